@@ -1,6 +1,6 @@
 use rayon::ThreadPoolBuilder;
 use serde::Serialize;
-use simstring_rust::{CharacterNgrams, Cosine, HashDb, Searcher};
+use simstring_rust::{CharacterNgrams, Cosine, Database, HashDb, Searcher};
 use std::env;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
@@ -149,6 +149,85 @@ fn bench_search(results: &mut Vec<BenchmarkResult>) {
     }
 }
 
+fn bench_concurrent_search(results: &mut Vec<BenchmarkResult>) {
+    setup_benchmark_environment();
+    let companies = load_companies();
+    let search_terms: Vec<String> = companies.iter().take(100).cloned().collect();
+    let threshold = 0.7;
+
+    let build_db = || {
+        let fe = Arc::new(CharacterNgrams::new(3, " "));
+        let mut db = HashDb::new(fe);
+        for company in &companies {
+            db.insert(company.clone());
+        }
+        db
+    };
+    // The mutex-guarded interner serializes query feature extraction; the frozen view does not
+    let db = build_db();
+    let frozen = build_db().freeze();
+
+    let views: [(&str, &dyn Database); 2] = [("mutex", &db), ("frozen", &frozen)];
+    for (view, database) in views {
+        let searcher = Searcher::new(database, Cosine);
+
+        for num_threads in [1, 2, 4, 8] {
+            let mut measurements = Vec::new();
+            let start_time = Instant::now();
+            let mut iteration = 0;
+
+            while start_time.elapsed() < Duration::from_secs(20) && iteration < 100 {
+                let start = Instant::now();
+                std::thread::scope(|scope| {
+                    for _ in 0..num_threads {
+                        scope.spawn(|| {
+                            for term in &search_terms {
+                                let _ = searcher.search(term, threshold).unwrap();
+                            }
+                        });
+                    }
+                });
+                let duration = start.elapsed();
+                measurements.push(duration.as_secs_f64() * 1000.0);
+                iteration += 1;
+            }
+
+            let mean = measurements.iter().sum::<f64>() / measurements.len() as f64;
+            let stddev = if measurements.len() > 1 {
+                let variance = measurements
+                    .iter()
+                    .map(|value| {
+                        let diff = mean - value;
+                        diff * diff
+                    })
+                    .sum::<f64>()
+                    / (measurements.len() - 1) as f64;
+                variance.sqrt()
+            } else {
+                0.0
+            };
+            let num_queries = num_threads * search_terms.len();
+
+            results.push(BenchmarkResult {
+                language: "rust".to_string(),
+                backend: "simstring-rust (native)".to_string(),
+                benchmark: "concurrent_search".to_string(),
+                parameters: serde_json::json!({
+                    "database": view,
+                    "threads": num_threads,
+                    "threshold": threshold,
+                    "queries_per_second": num_queries as f64 / (mean / 1000.0),
+                }),
+                stats: Stats {
+                    mean,
+                    stddev,
+                    iterations: measurements.len(),
+                },
+            });
+        }
+    }
+}
+
 fn load_companies() -> Vec<String> {
     let file_path = Path::new(env!("CARGO_MANIFEST_DIR"))
         .join("benches")
@@ -166,5 +245,6 @@ fn main() {
     let mut results = Vec::new();
     bench_insert(&mut results);
     bench_search(&mut results);
+    bench_concurrent_search(&mut results);
     println!("{}", serde_json::to_string_pretty(&results).unwrap());
 }
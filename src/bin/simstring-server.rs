@@ -0,0 +1,178 @@
+//! HTTP/JSON search service over a single database. See [`simstring_rust::server`] for the
+//! endpoints.
+
+use simstring_rust::database::{simstring_cpp, TextOptions};
+use simstring_rust::server::{SearchServer, ServerConfig};
+use simstring_rust::{CharacterNgrams, FeatureExtractor, HashDb, WordNgrams};
+use std::fs::File;
+use std::io::Read;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+const USAGE: &str = "\
+USAGE: simstring-server -d DB [OPTIONS]
+
+Serves similarity searches over DB, which is either a database saved by this crate,
+a database built by the C++ simstring tool, or a text file with one string per line.
+
+OPTIONS:
+  -d, --database=DB      database or text file to serve (required)
+  -a, --addr=ADDR        address to listen on (default: 127.0.0.1:8080)
+  -n, --ngram=N          n-gram size for text files (default: 3)
+  -w, --word             index text files with word n-grams instead of character n-grams
+  -s, --similarity=SIM   exact, dice, cosine, jaccard or overlap (default: cosine)
+  -t, --threshold=TH     default similarity threshold (default: 0.7)
+      --workers=N        number of request threads (default: available CPUs)
+  -h, --help             show this help message
+";
+
+const NATIVE_MAGIC: &[u8; 8] = b"SSRUSTDB";
+const CPP_MAGIC: &[u8; 4] = b"SSDB";
+
+struct Options {
+    database: String,
+    addr: String,
+    ngram: usize,
+    word: bool,
+    config: ServerConfig,
+    workers: usize,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        database: String::new(),
+        addr: "127.0.0.1:8080".to_string(),
+        ngram: 3,
+        word: false,
+        config: ServerConfig::default(),
+        workers: std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("option `{name}` requires a value"))
+        };
+        match name.as_str() {
+            "-d" | "--database" => options.database = value()?,
+            "-a" | "--addr" => options.addr = value()?,
+            "-n" | "--ngram" => {
+                options.ngram = value()?
+                    .parse()
+                    .map_err(|_| "n-gram size must be a positive integer".to_string())?;
+            }
+            "-w" | "--word" => options.word = true,
+            "-s" | "--similarity" => options.config.measure = value()?.parse()?,
+            "-t" | "--threshold" => {
+                options.config.threshold = value()?
+                    .parse()
+                    .map_err(|_| "threshold must be a number".to_string())?;
+            }
+            "--workers" => {
+                options.workers = value()?
+                    .parse()
+                    .map_err(|_| "number of workers must be a positive integer".to_string())?;
+            }
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("unknown option `{arg}`")),
+        }
+    }
+
+    if options.database.is_empty() {
+        return Err("a database file must be given with -d".to_string());
+    }
+    if options.ngram == 0 {
+        return Err("n-gram size must be a positive integer".to_string());
+    }
+    let threshold = options.config.threshold;
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        return Err(format!("threshold must be in (0, 1], but was {threshold}"));
+    }
+    Ok(Some(options))
+}
+
+fn load_database(options: &Options) -> Result<HashDb, String> {
+    let mut magic = [0u8; 8];
+    let mut file = File::open(&options.database).map_err(|e| e.to_string())?;
+    let len = file.read(&mut magic).map_err(|e| e.to_string())?;
+    let magic = &magic[..len];
+
+    if magic.starts_with(CPP_MAGIC) {
+        return simstring_cpp::read(&options.database).map_err(|e| e.to_string());
+    }
+    if magic == NATIVE_MAGIC {
+        // Saved databases record their extractor, so `-n` and `-w` do not apply
+        let descriptor = HashDb::read_descriptor(&options.database).map_err(|e| e.to_string())?;
+        let extractor: Arc<dyn FeatureExtractor> =
+            if let Some(extractor) = CharacterNgrams::from_descriptor(&descriptor) {
+                Arc::new(extractor)
+            } else if let Some(extractor) = WordNgrams::from_descriptor(&descriptor) {
+                Arc::new(extractor)
+            } else {
+                return Err(format!(
+                    "unsupported feature extractor in database: {descriptor}"
+                ));
+            };
+        return HashDb::load(&options.database, extractor).map_err(|e| e.to_string());
+    }
+
+    let extractor: Arc<dyn FeatureExtractor> = if options.word {
+        Arc::new(WordNgrams::new(options.ngram, " ", " "))
+    } else {
+        Arc::new(CharacterNgrams::new(options.ngram, "$"))
+    };
+
+    let text_options = TextOptions {
+        trim: true,
+        skip_blank: true,
+        dedup: true,
+    };
+    let (db, report) = HashDb::from_path(&options.database, extractor, &text_options)
+        .map_err(|e| e.to_string())?;
+    for line in &report.malformed {
+        eprintln!(
+            "simstring-server: skipped line {} ({:?})",
+            line.line_number, line.reason
+        );
+    }
+    Ok(db)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("simstring-server: {message}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let db = match load_database(&options) {
+        Ok(db) => db,
+        Err(message) => {
+            eprintln!("simstring-server: {}: {message}", options.database);
+            return ExitCode::FAILURE;
+        }
+    };
+    let http = match tiny_http::Server::http(&options.addr) {
+        Ok(http) => http,
+        Err(e) => {
+            eprintln!("simstring-server: cannot listen on {}: {e}", options.addr);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    eprintln!("simstring-server: listening on http://{}", options.addr);
+    SearchServer::new(db, options.config).serve(&http, options.workers);
+    ExitCode::SUCCESS
+}
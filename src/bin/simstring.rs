@@ -0,0 +1,252 @@
+//! Command-line front end compatible with the C++ `simstring` tool.
+//!
+//! Character databases are read and written in the C++ file layout, so databases can be
+//! shared with the original tool. Word n-gram databases (`-w`) use this crate's own format.
+
+use simstring_rust::database::simstring_cpp;
+use simstring_rust::{
+    Cosine, Dice, ExactMatch, HashDb, Jaccard, Measure, Overlap, Searcher, SimstringNgrams,
+    WordNgrams,
+};
+use std::fs::File;
+use std::io::{self, BufRead, Read, Write};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Instant;
+
+const USAGE: &str = "\
+USAGE: simstring [OPTIONS]
+
+Builds a database from strings read from STDIN (one per line) with -b, or
+otherwise reads query strings from STDIN and prints the similar strings.
+
+OPTIONS:
+  -b, --build            build a database from strings read from STDIN
+  -d, --database=DB      database file (required)
+  -u, --unicode          use Unicode characters instead of bytes for n-grams
+  -n, --ngram=N          n-gram size (default: 3)
+  -m, --mark             pad strings with begin/end marks
+  -w, --word             use word n-grams instead of character n-grams
+  -s, --similarity=SIM   exact, dice, cosine, jaccard or overlap (default: cosine)
+  -t, --threshold=TH     similarity threshold (default: 0.7)
+  -r, --ranked           print matches by descending score, with their scores
+  -e, --echo-back        print each query before its results
+  -q, --quiet            do not print progress and result counts
+  -h, --help             show this help message
+";
+
+/// Magic bytes of the databases written by `HashDb::save`.
+const NATIVE_MAGIC: &[u8; 8] = b"SSRUSTDB";
+
+struct Options {
+    build: bool,
+    database: String,
+    unicode: bool,
+    ngram: usize,
+    mark: bool,
+    word: bool,
+    similarity: String,
+    threshold: f64,
+    ranked: bool,
+    echo_back: bool,
+    quiet: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        build: false,
+        database: String::new(),
+        unicode: false,
+        ngram: 3,
+        mark: false,
+        word: false,
+        similarity: "cosine".to_string(),
+        threshold: 0.7,
+        ranked: false,
+        echo_back: false,
+        quiet: false,
+    };
+
+    while let Some(arg) = args.next() {
+        // Accept `--name=value`, `--name value`, `-xvalue` and `-x value`
+        let (name, inline_value) = if let Some(long) = arg.strip_prefix("--") {
+            match long.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (long.to_string(), None),
+            }
+        } else if let Some(short) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+            let mut chars = short.chars();
+            let flag = chars.next().unwrap().to_string();
+            let rest = chars.as_str();
+            (flag, (!rest.is_empty()).then(|| rest.to_string()))
+        } else {
+            return Err(format!("unexpected argument `{arg}`"));
+        };
+
+        let mut value = |name: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("option `{name}` requires a value"))
+        };
+        match name.as_str() {
+            "b" | "build" => options.build = true,
+            "d" | "database" => options.database = value("--database")?,
+            "u" | "unicode" => options.unicode = true,
+            "n" | "ngram" => {
+                options.ngram = value("--ngram")?
+                    .parse()
+                    .map_err(|_| "n-gram size must be a positive integer".to_string())?;
+            }
+            "m" | "mark" => options.mark = true,
+            "w" | "word" => options.word = true,
+            "s" | "similarity" => options.similarity = value("--similarity")?,
+            "t" | "threshold" => {
+                options.threshold = value("--threshold")?
+                    .parse()
+                    .map_err(|_| "threshold must be a number".to_string())?;
+            }
+            "r" | "ranked" => options.ranked = true,
+            "e" | "echo-back" => options.echo_back = true,
+            "q" | "quiet" => options.quiet = true,
+            "h" | "help" => return Ok(None),
+            _ => return Err(format!("unknown option `{arg}`")),
+        }
+    }
+
+    if options.database.is_empty() {
+        return Err("a database file must be given with -d".to_string());
+    }
+    if options.ngram == 0 {
+        return Err("n-gram size must be a positive integer".to_string());
+    }
+    Ok(Some(options))
+}
+
+fn build(options: &Options) -> Result<(), String> {
+    let start = Instant::now();
+    let lines: Vec<String> = io::stdin()
+        .lock()
+        .lines()
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("failed to read STDIN: {e}"))?;
+    let num_lines = lines.len();
+
+    if options.word {
+        let extractor = Arc::new(WordNgrams::new(options.ngram, " ", " "));
+        let db = HashDb::from_iter_parallel(extractor, lines);
+        db.save(&options.database).map_err(|e| e.to_string())?;
+    } else {
+        let ngrams = SimstringNgrams::new(options.ngram, options.mark, options.unicode);
+        simstring_cpp::write(&lines, &options.database, &ngrams).map_err(|e| e.to_string())?;
+    }
+
+    if !options.quiet {
+        println!(
+            "Inserted {} strings into {} ({:.3} sec)",
+            num_lines,
+            options.database,
+            start.elapsed().as_secs_f64()
+        );
+    }
+    Ok(())
+}
+
+fn open_database(options: &Options) -> Result<HashDb, String> {
+    let mut magic = [0u8; 8];
+    let is_native = File::open(&options.database)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .map(|_| &magic == NATIVE_MAGIC)
+        .unwrap_or(false);
+
+    if is_native {
+        // Word-mode databases record their extractor, so `-n` need not be repeated
+        let descriptor = HashDb::read_descriptor(&options.database).map_err(|e| e.to_string())?;
+        let extractor = WordNgrams::from_descriptor(&descriptor)
+            .ok_or_else(|| format!("unsupported feature extractor in database: {descriptor}"))?;
+        HashDb::load(&options.database, Arc::new(extractor)).map_err(|e| e.to_string())
+    } else {
+        simstring_cpp::read(&options.database).map_err(|e| e.to_string())
+    }
+}
+
+fn write_error(e: io::Error) -> String {
+    format!("failed to write to STDOUT: {e}")
+}
+
+fn retrieve<M: Measure>(db: &HashDb, measure: M, options: &Options) -> Result<(), String> {
+    let searcher = Searcher::new(db, measure);
+    let mut out = io::stdout().lock();
+
+    for line in io::stdin().lock().lines() {
+        let query = line.map_err(|e| format!("failed to read STDIN: {e}"))?;
+        let start = Instant::now();
+
+        if options.echo_back {
+            writeln!(out, "{query}").map_err(write_error)?;
+        }
+        let num_results = if options.ranked {
+            let results = searcher
+                .ranked_search(&query, options.threshold)
+                .map_err(|e| e.to_string())?;
+            for (text, score) in &results {
+                writeln!(out, "\t{text}\t{score:.6}").map_err(write_error)?;
+            }
+            results.len()
+        } else {
+            let results = searcher
+                .search(&query, options.threshold)
+                .map_err(|e| e.to_string())?;
+            for text in &results {
+                writeln!(out, "\t{text}").map_err(write_error)?;
+            }
+            results.len()
+        };
+
+        if !options.quiet {
+            let elapsed = start.elapsed().as_secs_f64();
+            writeln!(out, "{num_results} strings retrieved ({elapsed:.6} sec)")
+                .map_err(write_error)?;
+        }
+        out.flush().map_err(write_error)?;
+    }
+    Ok(())
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    if options.build {
+        return build(options);
+    }
+
+    let db = open_database(options)?;
+    match options.similarity.as_str() {
+        "exact" => retrieve(&db, ExactMatch, options),
+        "dice" => retrieve(&db, Dice, options),
+        "cosine" => retrieve(&db, Cosine, options),
+        "jaccard" => retrieve(&db, Jaccard, options),
+        "overlap" => retrieve(&db, Overlap, options),
+        other => Err(format!("unknown similarity measure `{other}`")),
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("simstring: {message}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("simstring: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::database::{Database, StringId};
+use crate::join::self_join;
+use crate::measures::Measure;
+use crate::search::SearchError;
+use rustc_hash::FxHashMap;
+
+/// A group of similar strings and the member chosen to stand for them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    pub representative: StringId,
+    /// Member ids in ascending order, including the representative.
+    pub members: Vec<StringId>,
+}
+
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+}
+
+fn live_ids(db: &dyn Database) -> impl Iterator<Item = StringId> + '_ {
+    (0..db.string_id_bound()).filter(|&id| db.get_string(id).is_some())
+}
+
+/// Groups the strings of `db` into the connected components of the graph linking every pair
+/// with similarity of at least `alpha`.
+///
+/// Components are transitive, so a chain of close strings ends up in one cluster even when its
+/// ends are dissimilar. The representative is the member with the highest summed similarity to
+/// its neighbours, ties going to the lowest id. Strings without neighbours form singleton
+/// clusters. Clusters are ordered by their lowest member id.
+pub fn connected_components<M: Measure>(
+    db: &dyn Database,
+    measure: M,
+    alpha: f64,
+) -> Result<Vec<Cluster>, SearchError> {
+    let pairs = self_join(db, measure, alpha)?;
+
+    let bound = db.string_id_bound();
+    let mut components = DisjointSet::new(bound);
+    let mut centrality = vec![0.0; bound];
+    for &(a, b, score) in &pairs {
+        components.union(a, b);
+        centrality[a] += score;
+        centrality[b] += score;
+    }
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    let mut cluster_of_root: FxHashMap<usize, usize> = FxHashMap::default();
+    for id in live_ids(db) {
+        let root = components.find(id);
+        let index = *cluster_of_root.entry(root).or_insert_with(|| {
+            clusters.push(Cluster {
+                representative: id,
+                members: Vec::new(),
+            });
+            clusters.len() - 1
+        });
+
+        let cluster = &mut clusters[index];
+        if centrality[id] > centrality[cluster.representative] {
+            cluster.representative = id;
+        }
+        cluster.members.push(id);
+    }
+    Ok(clusters)
+}
+
+/// Greedily clusters the strings of `db` around leaders.
+///
+/// Strings are visited in id order. Each one joins the most similar existing leader scoring at
+/// least `alpha` (ties going to the earlier leader), or otherwise becomes the leader of a new
+/// cluster. Unlike [`connected_components`], every member is within `alpha` of its
+/// representative, which is the cluster's leader. Clusters are ordered by leader id.
+pub fn leader_clusters<M: Measure>(
+    db: &dyn Database,
+    measure: M,
+    alpha: f64,
+) -> Result<Vec<Cluster>, SearchError> {
+    let pairs = self_join(db, measure, alpha)?;
+
+    // Pairs are sorted by `(a, b)` with `a < b`; index them by the later string
+    let mut earlier: FxHashMap<StringId, Vec<(StringId, f64)>> = FxHashMap::default();
+    for (a, b, score) in pairs {
+        earlier.entry(b).or_default().push((a, score));
+    }
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    let mut cluster_of_leader: FxHashMap<StringId, usize> = FxHashMap::default();
+    for id in live_ids(db) {
+        let mut best: Option<(usize, f64)> = None;
+        for &(other, score) in earlier.get(&id).into_iter().flatten() {
+            if let Some(&index) = cluster_of_leader.get(&other) {
+                if best.is_none_or(|(_, best_score)| score > best_score) {
+                    best = Some((index, score));
+                }
+            }
+        }
+
+        match best {
+            Some((index, _)) => clusters[index].members.push(id),
+            None => {
+                cluster_of_leader.insert(id, clusters.len());
+                clusters.push(Cluster {
+                    representative: id,
+                    members: vec![id],
+                });
+            }
+        }
+    }
+    Ok(clusters)
+}
@@ -0,0 +1,145 @@
+//! Minimal reader/writer for the cdb++ constant database format used by the C++ simstring
+//! tool for its per-size n-gram indexes.
+
+use super::persist::PersistError;
+use std::io::{self, Write};
+
+const CHUNK_ID: &[u8; 4] = b"CDB+";
+const VERSION: u32 = 1;
+pub(crate) const BYTEORDER_CHECK: u32 = 0x6244_5371;
+const NUM_TABLES: usize = 256;
+const OFFSET_REFS: usize = 16;
+const OFFSET_DATA: usize = OFFSET_REFS + NUM_TABLES * 8;
+const HASH_SEED: u32 = 0x8765_4321;
+
+fn murmurhash2(key: &[u8], seed: u32) -> u32 {
+    const M: u32 = 0x5bd1_e995;
+    const R: u32 = 24;
+
+    let mut h = seed ^ key.len() as u32;
+    let mut chunks = key.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    let tail = chunks.remainder();
+    if tail.len() >= 3 {
+        h ^= (tail[2] as u32) << 16;
+    }
+    if tail.len() >= 2 {
+        h ^= (tail[1] as u32) << 8;
+    }
+    if !tail.is_empty() {
+        h ^= tail[0] as u32;
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+    h
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32, PersistError> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| PersistError::Corrupt(format!("cdb++ offset {offset} out of bounds")))
+}
+
+fn slice_at(data: &[u8], offset: usize, len: usize) -> Result<&[u8], PersistError> {
+    data.get(offset..offset + len)
+        .ok_or_else(|| PersistError::Corrupt(format!("cdb++ record at {offset} out of bounds")))
+}
+
+/// A `(key, value)` pair borrowed from a cdb++ chunk.
+pub(crate) type Record<'a> = (&'a [u8], &'a [u8]);
+
+/// Returns every record stored in a cdb++ chunk.
+pub(crate) fn read_records(data: &[u8]) -> Result<Vec<Record<'_>>, PersistError> {
+    if data.len() < OFFSET_DATA || &data[..4] != CHUNK_ID {
+        return Err(PersistError::Corrupt("not a cdb++ file".to_string()));
+    }
+    if u32_at(data, 12)? != BYTEORDER_CHECK {
+        return Err(PersistError::Unsupported(
+            "cdb++ file has a foreign byte order".to_string(),
+        ));
+    }
+
+    let mut records = Vec::new();
+    for table in 0..NUM_TABLES {
+        let table_offset = u32_at(data, OFFSET_REFS + table * 8)? as usize;
+        let num_buckets = u32_at(data, OFFSET_REFS + table * 8 + 4)? as usize;
+        for bucket in 0..num_buckets {
+            let record_offset = u32_at(data, table_offset + bucket * 8 + 4)? as usize;
+            if record_offset == 0 {
+                continue;
+            }
+            let key_len = u32_at(data, record_offset)? as usize;
+            let key = slice_at(data, record_offset + 4, key_len)?;
+            let value_len = u32_at(data, record_offset + 4 + key_len)? as usize;
+            let value = slice_at(data, record_offset + 8 + key_len, value_len)?;
+            records.push((key, value));
+        }
+    }
+    Ok(records)
+}
+
+/// Writes `records` as a cdb++ chunk. Keys are expected to be unique.
+pub(crate) fn write_records<W: Write>(
+    writer: &mut W,
+    records: &[(Vec<u8>, Vec<u8>)],
+) -> io::Result<()> {
+    let mut data = Vec::new();
+    let mut tables: Vec<Vec<(u32, u32)>> = vec![Vec::new(); NUM_TABLES];
+    for (key, value) in records {
+        let offset = (OFFSET_DATA + data.len()) as u32;
+        let hash = murmurhash2(key, HASH_SEED);
+        tables[hash as usize % NUM_TABLES].push((hash, offset));
+
+        data.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        data.extend_from_slice(key);
+        data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        data.extend_from_slice(value);
+    }
+
+    let mut refs = Vec::with_capacity(NUM_TABLES);
+    let mut table_data = Vec::new();
+    for buckets in &tables {
+        // Open addressing with twice as many slots as entries, as in cdb++.
+        let num_slots = buckets.len() * 2;
+        let mut slots = vec![(0u32, 0u32); num_slots];
+        for &(hash, offset) in buckets {
+            let mut slot = (hash >> 8) as usize % num_slots;
+            while slots[slot].1 != 0 {
+                slot = (slot + 1) % num_slots;
+            }
+            slots[slot] = (hash, offset);
+        }
+
+        refs.push((
+            (OFFSET_DATA + data.len() + table_data.len()) as u32,
+            num_slots as u32,
+        ));
+        for (hash, offset) in slots {
+            table_data.extend_from_slice(&hash.to_le_bytes());
+            table_data.extend_from_slice(&offset.to_le_bytes());
+        }
+    }
+
+    let total_size = OFFSET_DATA + data.len() + table_data.len();
+    writer.write_all(CHUNK_ID)?;
+    writer.write_all(&(total_size as u32).to_le_bytes())?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&BYTEORDER_CHECK.to_le_bytes())?;
+    for (offset, num) in refs {
+        writer.write_all(&offset.to_le_bytes())?;
+        writer.write_all(&num.to_le_bytes())?;
+    }
+    writer.write_all(&data)?;
+    writer.write_all(&table_data)
+}
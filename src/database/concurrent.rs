@@ -0,0 +1,174 @@
+use super::{Database, HashDb, StringId};
+use crate::extractors::FeatureExtractor;
+use crate::measures::Measure;
+use crate::search::{by_score_desc, check_threshold, SearchError, Searcher};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+/// A database that accepts inserts through a shared reference while searches are running.
+///
+/// Strings are spread round-robin over shards, each a [`HashDb`] behind its own `RwLock`. An
+/// insert only write-locks one shard, so searches keep running on the others, and a search
+/// read-locks each shard just while probing it.
+///
+/// # Visibility
+///
+/// A string is visible to every search that starts after its `insert` call returned. A search
+/// running concurrently with an insert may or may not return the new string. Shards are locked
+/// one at a time, so a search sees a consistent state of each shard, but not a snapshot of the
+/// whole database: of two concurrent inserts it may observe either one alone.
+///
+/// Shards are searched in parallel, but each shard's search runs on a single thread, so no rayon
+/// work is ever waited on while a lock is held. Inserts and searches may therefore be issued
+/// from inside the rayon pool.
+///
+/// Because search results cannot borrow from a locked shard, they are returned as owned
+/// `String`s. For the same reason this type does not implement [`Database`],
+/// whose accessors return references; use [`HashDb`] or [`FrozenHashDb`](super::FrozenHashDb)
+/// for top-k, batch searches and joins.
+pub struct ConcurrentHashDb {
+    shards: Vec<RwLock<HashDb>>,
+    next_shard: AtomicUsize,
+}
+
+impl ConcurrentHashDb {
+    /// Creates a database with one shard per available CPU.
+    pub fn new(feature_extractor: Arc<dyn FeatureExtractor>) -> Self {
+        let num_shards = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_shards(feature_extractor, num_shards)
+    }
+
+    /// Creates a database with `num_shards` shards (at least one).
+    pub fn with_shards(feature_extractor: Arc<dyn FeatureExtractor>, num_shards: usize) -> Self {
+        let shards = (0..num_shards.max(1))
+            .map(|_| RwLock::new(HashDb::new(Arc::clone(&feature_extractor))))
+            .collect();
+        Self {
+            shards,
+            next_shard: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn num_shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Inserts `text` and returns its id.
+    ///
+    /// Ids are unique and stable, but not contiguous nor ordered by insertion time.
+    pub fn insert(&self, text: String) -> StringId {
+        let shard = self.next_shard.fetch_add(1, Ordering::Relaxed) % self.shards.len();
+        let mut db = self.shards[shard].write().unwrap();
+        let local_id = db.strings.len();
+        db.insert(text);
+        self.global_id(shard, local_id)
+    }
+
+    pub fn get_string(&self, id: StringId) -> Option<String> {
+        let (shard, local_id) = self.local_id(id);
+        let db = self.shards[shard].read().unwrap();
+        db.strings.get(local_id).cloned()
+    }
+
+    pub fn total_strings(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().unwrap().strings.len())
+            .sum()
+    }
+
+    /// Returns the strings similar to `query_string`, sorted like [`Searcher::search`].
+    pub fn search<M: Measure + Clone>(
+        &self,
+        measure: M,
+        query_string: &str,
+        alpha: f64,
+    ) -> Result<Vec<String>, SearchError> {
+        let mut results: Vec<String> = self
+            .ranked_matches(measure, query_string, alpha)?
+            .into_iter()
+            .map(|(_, text, _)| text)
+            .collect();
+
+        results.sort_unstable();
+        Ok(results)
+    }
+
+    /// Returns the strings similar to `query_string` with their scores, sorted like
+    /// [`Searcher::ranked_search`].
+    pub fn ranked_search<M: Measure + Clone>(
+        &self,
+        measure: M,
+        query_string: &str,
+        alpha: f64,
+    ) -> Result<Vec<(String, f64)>, SearchError> {
+        let mut results: Vec<(String, f64)> = self
+            .ranked_matches(measure, query_string, alpha)?
+            .into_iter()
+            .map(|(_, text, score)| (text, score))
+            .collect();
+
+        results.sort_unstable_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(results)
+    }
+
+    /// Returns the ids of the matches with their scores, sorted by descending score, ties broken
+    /// by ascending id.
+    pub fn ranked_search_ids<M: Measure + Clone>(
+        &self,
+        measure: M,
+        query_string: &str,
+        alpha: f64,
+    ) -> Result<Vec<(StringId, f64)>, SearchError> {
+        let mut results: Vec<(StringId, f64)> = self
+            .ranked_matches(measure, query_string, alpha)?
+            .into_iter()
+            .map(|(id, _, score)| (id, score))
+            .collect();
+
+        results.sort_unstable_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(results)
+    }
+
+    /// Searches every shard in parallel, translating shard-local ids to global ids.
+    fn ranked_matches<M: Measure + Clone>(
+        &self,
+        measure: M,
+        query_string: &str,
+        alpha: f64,
+    ) -> Result<Vec<(StringId, String, f64)>, SearchError> {
+        check_threshold(alpha)?;
+
+        let per_shard: Vec<Vec<(StringId, String, f64)>> = self
+            .shards
+            .par_iter()
+            .enumerate()
+            .map(|(shard, db)| {
+                // Rayon may hand a waiting thread any queued task, including an insert into
+                // this shard, so no rayon work runs while the read lock is held
+                let db = db.read().unwrap();
+                let searcher = Searcher::new(&*db, measure.clone());
+                let query_features = db.query_features(query_string);
+                searcher
+                    .sequential_scored_candidates(&query_features, alpha)
+                    .into_iter()
+                    .map(|(local_id, text, score)| {
+                        (self.global_id(shard, local_id), text.to_string(), score)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(per_shard.into_iter().flatten().collect())
+    }
+
+    fn global_id(&self, shard: usize, local_id: StringId) -> StringId {
+        local_id * self.shards.len() + shard
+    }
+
+    fn local_id(&self, id: StringId) -> (usize, StringId) {
+        (id % self.shards.len(), id / self.shards.len())
+    }
+}
@@ -0,0 +1,89 @@
+use super::StringId;
+use lasso::Spur;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::fmt;
+
+pub(super) type FeatureMap = FxHashMap<usize, FxHashMap<Spur, FxHashSet<StringId>>>;
+
+/// Borrowed view of the storage shared by [`HashDb`](super::HashDb) and
+/// [`FrozenHashDb`](super::FrozenHashDb), so both read it the same way.
+pub(super) struct Entries<'a, P> {
+    pub strings: &'a [String],
+    pub string_features: &'a [Vec<Spur>],
+    pub feature_map: &'a FeatureMap,
+    pub tombstones: &'a FxHashSet<StringId>,
+    pub payloads: &'a [P],
+}
+
+impl<'a, P> Entries<'a, P> {
+    pub fn lookup_strings(&self, size: usize, feature: Spur) -> Option<&'a FxHashSet<StringId>> {
+        self.feature_map.get(&size)?.get(&feature)
+    }
+
+    pub fn get_string(&self, id: StringId) -> Option<&'a str> {
+        if self.is_removed(id) {
+            return None;
+        }
+        self.strings.get(id).map(String::as_str)
+    }
+
+    pub fn get_features(&self, id: StringId) -> Option<&'a Vec<Spur>> {
+        if self.is_removed(id) {
+            return None;
+        }
+        self.string_features.get(id)
+    }
+
+    pub fn payload(&self, id: StringId) -> Option<&'a P> {
+        if self.is_removed(id) {
+            return None;
+        }
+        self.payloads.get(id)
+    }
+
+    pub fn is_removed(&self, id: StringId) -> bool {
+        self.tombstones.contains(&id)
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = (StringId, &'a str)> {
+        let tombstones = self.tombstones;
+        self.strings
+            .iter()
+            .enumerate()
+            .filter(move |(id, _)| !tombstones.contains(id))
+            .map(|(id, text)| (id, text.as_str()))
+    }
+
+    pub fn max_feature_len(&self) -> usize {
+        self.feature_map.keys().max().copied().unwrap_or(0)
+    }
+
+    pub fn total_strings(&self) -> usize {
+        self.strings.len() - self.tombstones.len()
+    }
+
+    pub fn string_id_bound(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn fmt_debug(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        name: &str,
+        features_interned: usize,
+    ) -> fmt::Result {
+        let total_unique_features: usize = self
+            .feature_map
+            .values()
+            .map(|size_map| size_map.len())
+            .sum();
+
+        f.debug_struct(name)
+            .field("num_strings", &self.total_strings())
+            .field("num_removed", &self.tombstones.len())
+            .field("num_feature_size_buckets", &self.feature_map.len())
+            .field("total_unique_features_interned", &features_interned)
+            .field("total_unique_features_indexed", &total_unique_features)
+            .finish()
+    }
+}
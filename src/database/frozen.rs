@@ -0,0 +1,132 @@
+use super::entries::{Entries, FeatureMap};
+use super::{Database, HashDb, StringId};
+use crate::extractors::FeatureExtractor;
+use lasso::{Rodeo, RodeoReader, Spur};
+use rustc_hash::FxHashSet;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+/// An immutable view of a [`HashDb`], created with [`HashDb::freeze`].
+///
+/// The interner is a read-only [`RodeoReader`], so queries extract their features without
+/// taking any lock and any number of threads can search the same database concurrently.
+/// Call [`FrozenHashDb::thaw`] to get a mutable [`HashDb`] back.
+pub struct FrozenHashDb<P = ()> {
+    feature_extractor: Arc<dyn FeatureExtractor>,
+    strings: Vec<String>,
+    string_features: Vec<Vec<Spur>>,
+    feature_map: FeatureMap,
+    interner: RodeoReader,
+    tombstones: FxHashSet<StringId>,
+    payloads: Vec<P>,
+}
+
+impl<P> fmt::Debug for FrozenHashDb<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.entries()
+            .fmt_debug(f, "FrozenHashDb", self.interner.len())
+    }
+}
+
+impl<P> FrozenHashDb<P> {
+    pub(super) fn from_parts(
+        feature_extractor: Arc<dyn FeatureExtractor>,
+        strings: Vec<String>,
+        string_features: Vec<Vec<Spur>>,
+        feature_map: FeatureMap,
+        interner: RodeoReader,
+        tombstones: FxHashSet<StringId>,
+        payloads: Vec<P>,
+    ) -> Self {
+        Self {
+            feature_extractor,
+            strings,
+            string_features,
+            feature_map,
+            interner,
+            tombstones,
+            payloads,
+        }
+    }
+
+    /// Turns the view back into a mutable [`HashDb`], keeping all ids and feature ids.
+    pub fn thaw(self) -> HashDb<P> {
+        // Re-interning in key order reproduces the same keys
+        let mut interner = Rodeo::default();
+        for (_, feature) in self.interner.iter() {
+            interner.get_or_intern(feature);
+        }
+
+        HashDb::from_parts(
+            self.feature_extractor,
+            self.strings,
+            self.string_features,
+            self.feature_map,
+            interner,
+            self.tombstones,
+            self.payloads,
+        )
+    }
+
+    fn entries(&self) -> Entries<'_, P> {
+        Entries {
+            strings: &self.strings,
+            string_features: &self.string_features,
+            feature_map: &self.feature_map,
+            tombstones: &self.tombstones,
+            payloads: &self.payloads,
+        }
+    }
+
+    pub fn payload(&self, id: StringId) -> Option<&P> {
+        self.entries().payload(id)
+    }
+
+    /// Iterates over the live (not removed) strings and their ids.
+    pub fn iter(&self) -> impl Iterator<Item = (StringId, &str)> {
+        self.entries().iter()
+    }
+
+    pub fn is_removed(&self, id: StringId) -> bool {
+        self.entries().is_removed(id)
+    }
+}
+
+impl<P: Send + Sync + 'static> Database for FrozenHashDb<P> {
+    fn lookup_strings(&self, size: usize, feature: Spur) -> Option<&FxHashSet<StringId>> {
+        self.entries().lookup_strings(size, feature)
+    }
+
+    fn get_string(&self, id: StringId) -> Option<&str> {
+        self.entries().get_string(id)
+    }
+
+    fn get_features(&self, id: StringId) -> Option<&Vec<Spur>> {
+        self.entries().get_features(id)
+    }
+
+    fn feature_extractor(&self) -> &dyn FeatureExtractor {
+        &*self.feature_extractor
+    }
+
+    fn max_feature_len(&self) -> usize {
+        self.entries().max_feature_len()
+    }
+
+    fn total_strings(&self) -> usize {
+        self.entries().total_strings()
+    }
+
+    fn string_id_bound(&self) -> usize {
+        self.entries().string_id_bound()
+    }
+
+    fn feature_id(&self, feature: &str) -> Option<Spur> {
+        self.interner.get(feature)
+    }
+
+    fn get_payload(&self, id: StringId) -> Option<&dyn Any> {
+        self.payload(id).map(|payload| payload as &dyn Any)
+    }
+}
@@ -1,90 +1,577 @@
-use crate::database::{Database, StringId};
+use super::entries::{Entries, FeatureMap};
+use super::frozen::FrozenHashDb;
+use super::persist::{self, PersistError};
+use crate::database::{Database, DatabaseMut, StringId};
 use crate::extractors::FeatureExtractor;
-use lasso::{Rodeo, Spur};
+use lasso::{Key, Rodeo, Spur};
+use rayon::prelude::*;
 use rustc_hash::{FxHashMap, FxHashSet};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::Any;
 use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
-pub struct HashDb {
+/// An in-memory database of strings, optionally carrying a user payload `P` per string.
+pub struct HashDb<P = ()> {
     feature_extractor: Arc<dyn FeatureExtractor>,
+    /// Strings indexed by id. Removed entries are left empty until `compact` is called.
     pub strings: Vec<String>,
     string_features: Vec<Vec<Spur>>,
-    feature_map: FxHashMap<usize, FxHashMap<Spur, FxHashSet<StringId>>>,
+    feature_map: FeatureMap,
     interner: Arc<Mutex<Rodeo>>,
+    // Removed ids keep their slot (with an empty string) until `compact` is called
+    tombstones: FxHashSet<StringId>,
+    payloads: Vec<P>,
 }
 
-impl fmt::Debug for HashDb {
+impl<P> fmt::Debug for HashDb<P> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let total_unique_features: usize = self
-            .feature_map
-            .values()
-            .map(|size_map| size_map.len())
-            .sum();
-        let interner = self.interner.lock().unwrap();
-
-        f.debug_struct("HashDb")
-            .field("num_strings", &self.strings.len())
-            .field("num_feature_size_buckets", &self.feature_map.len())
-            .field("total_unique_features_interned", &interner.len())
-            .field("total_unique_features_indexed", &total_unique_features)
-            .finish()
+        let features_interned = self.interner.lock().unwrap().len();
+        self.entries().fmt_debug(f, "HashDb", features_interned)
     }
 }
 
 impl HashDb {
     pub fn new(feature_extractor: Arc<dyn FeatureExtractor>) -> Self {
+        Self::with_payloads(feature_extractor)
+    }
+
+    /// Builds a database from `texts`, extracting features on the rayon thread pool.
+    ///
+    /// The result is identical to inserting `texts` one by one, down to string and feature ids.
+    pub fn from_iter_parallel<I>(feature_extractor: Arc<dyn FeatureExtractor>, texts: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut db = Self::new(feature_extractor);
+        db.extend_parallel(texts);
+        db
+    }
+
+    /// Loads a database previously written with [`HashDb::save`].
+    ///
+    /// `feature_extractor` must be configured exactly like the extractor the database was
+    /// built with, otherwise [`PersistError::ExtractorMismatch`] is returned.
+    pub fn load(
+        path: impl AsRef<Path>,
+        feature_extractor: Arc<dyn FeatureExtractor>,
+    ) -> Result<Self, PersistError> {
+        Self::load_with_payloads(path, feature_extractor)
+    }
+
+    /// Returns the descriptor of the extractor a database was saved with, without loading it.
+    pub fn read_descriptor(path: impl AsRef<Path>) -> Result<String, PersistError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        persist::read_header(&mut reader)
+    }
+
+    pub fn read_from<R: Read>(
+        reader: &mut R,
+        feature_extractor: Arc<dyn FeatureExtractor>,
+    ) -> Result<Self, PersistError> {
+        Self::read_with_payloads(reader, feature_extractor)
+    }
+}
+
+impl<P: Default> HashDb<P> {
+    /// Inserts `text` with a default payload.
+    pub fn insert(&mut self, text: String) {
+        self.insert_with_payload(text, P::default());
+    }
+
+    /// Inserts every string of `texts` with a default payload, extracting features in parallel.
+    ///
+    /// Ids are assigned in iteration order, exactly as repeated [`HashDb::insert`] calls would.
+    pub fn extend_parallel<I>(&mut self, texts: I)
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.extend_parallel_with_payloads(texts.into_iter().map(|text| (text, P::default())));
+    }
+}
+
+impl<P> HashDb<P> {
+    /// Creates an empty database whose strings each carry a payload of type `P`.
+    pub fn with_payloads(feature_extractor: Arc<dyn FeatureExtractor>) -> Self {
         Self {
             feature_extractor,
             strings: Vec::new(),
             string_features: Vec::new(),
             feature_map: FxHashMap::default(),
             interner: Arc::new(Mutex::new(Rodeo::default())),
+            tombstones: FxHashSet::default(),
+            payloads: Vec::new(),
         }
     }
 
-    pub fn insert(&mut self, text: String) {
-        Database::insert(self, text);
-    }
-
-    pub fn clear(&mut self) {
-        Database::clear(self);
+    pub(crate) fn from_parts(
+        feature_extractor: Arc<dyn FeatureExtractor>,
+        strings: Vec<String>,
+        string_features: Vec<Vec<Spur>>,
+        feature_map: FeatureMap,
+        interner: Rodeo,
+        tombstones: FxHashSet<StringId>,
+        payloads: Vec<P>,
+    ) -> Self {
+        Self {
+            feature_extractor,
+            strings,
+            string_features,
+            feature_map,
+            interner: Arc::new(Mutex::new(interner)),
+            tombstones,
+            payloads,
+        }
     }
-}
 
-impl Database for HashDb {
-    fn insert(&mut self, text: String) {
-        let mut interner = self.interner.lock().unwrap();
-        let features = self.feature_extractor.features(&text, &mut interner);
-        let size = features.len();
+    /// Inserts `text` along with a payload that search results can refer back to.
+    pub fn insert_with_payload(&mut self, text: String, payload: P) {
+        let features = {
+            let mut interner = self.interner.lock().unwrap();
+            self.feature_extractor.features(&text, &mut interner)
+        };
         let string_id = self.strings.len();
 
+        self.link(string_id, &features);
         self.strings.push(text);
-        self.string_features.push(features.clone());
+        self.string_features.push(features);
+        self.payloads.push(payload);
+    }
 
-        let size_map = self.feature_map.entry(size).or_default();
-        for feature in features {
-            size_map.entry(feature).or_default().insert(string_id);
+    /// Builds a database from `(text, payload)` pairs, extracting features on the rayon
+    /// thread pool.
+    pub fn from_iter_parallel_with_payloads<I>(
+        feature_extractor: Arc<dyn FeatureExtractor>,
+        entries: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = (String, P)>,
+    {
+        let mut db = Self::with_payloads(feature_extractor);
+        db.extend_parallel_with_payloads(entries);
+        db
+    }
+
+    /// Inserts every `(text, payload)` pair of `entries`, extracting features in parallel.
+    ///
+    /// Ids are assigned in iteration order, exactly as repeated
+    /// [`HashDb::insert_with_payload`] calls would.
+    pub fn extend_parallel_with_payloads<I>(&mut self, entries: I)
+    where
+        I: IntoIterator<Item = (String, P)>,
+    {
+        let (texts, payloads): (Vec<String>, Vec<P>) = entries.into_iter().unzip();
+        if texts.is_empty() {
+            return;
+        }
+        let chunk_size = texts
+            .len()
+            .div_ceil(rayon::current_num_threads() * 4)
+            .max(1);
+
+        // Extract each chunk into its own interner, so no lock is shared between threads
+        let extractor = &self.feature_extractor;
+        let chunks: Vec<(Rodeo, Vec<Vec<Spur>>)> = texts
+            .par_chunks(chunk_size)
+            .map(|chunk| {
+                let mut interner = Rodeo::default();
+                let features = chunk
+                    .iter()
+                    .map(|text| extractor.features(text, &mut interner))
+                    .collect();
+                (interner, features)
+            })
+            .collect();
+
+        // A chunk interner holds features in first-seen order, so interning chunk by chunk
+        // assigns the same keys as sequential insertion would
+        let remaps: Vec<Vec<Spur>> = {
+            let mut interner = self.interner.lock().unwrap();
+            chunks
+                .iter()
+                .map(|(chunk_interner, _)| {
+                    chunk_interner
+                        .iter()
+                        .map(|(_, feature)| interner.get_or_intern(feature))
+                        .collect()
+                })
+                .collect()
+        };
+
+        let features: Vec<Vec<Spur>> = chunks
+            .into_par_iter()
+            .zip(remaps.par_iter())
+            .flat_map_iter(|((_, chunk_features), remap)| {
+                chunk_features.into_iter().map(move |features| {
+                    let mut features: Vec<Spur> = features
+                        .into_iter()
+                        .map(|feature| remap[feature.into_usize()])
+                        .collect();
+                    features.sort_unstable();
+                    features
+                })
+            })
+            .collect();
+
+        for ((text, features), payload) in texts.into_iter().zip(features).zip(payloads) {
+            let string_id = self.strings.len();
+            self.link(string_id, &features);
+            self.strings.push(text);
+            self.string_features.push(features);
+            self.payloads.push(payload);
         }
     }
 
-    fn clear(&mut self) {
+    pub fn clear(&mut self) {
         self.strings.clear();
         self.string_features.clear();
         self.feature_map.clear();
+        self.tombstones.clear();
+        self.payloads.clear();
         // clear the interner to release memory
         self.interner.lock().unwrap().clear();
     }
 
+    pub fn interner(&self) -> Arc<Mutex<Rodeo>> {
+        Arc::clone(&self.interner)
+    }
+
+    /// Converts the database into an immutable [`FrozenHashDb`] that can be searched from
+    /// many threads without locking.
+    pub fn freeze(self) -> FrozenHashDb<P> {
+        let interner = Arc::try_unwrap(self.interner)
+            .map(|interner| interner.into_inner().unwrap())
+            .unwrap_or_else(|shared| shared.lock().unwrap().clone());
+
+        FrozenHashDb::from_parts(
+            self.feature_extractor,
+            self.strings,
+            self.string_features,
+            self.feature_map,
+            interner.into_reader(),
+            self.tombstones,
+            self.payloads,
+        )
+    }
+
+    fn entries(&self) -> Entries<'_, P> {
+        Entries {
+            strings: &self.strings,
+            string_features: &self.string_features,
+            feature_map: &self.feature_map,
+            tombstones: &self.tombstones,
+            payloads: &self.payloads,
+        }
+    }
+
+    pub fn payload(&self, id: StringId) -> Option<&P> {
+        self.entries().payload(id)
+    }
+
+    pub fn payload_mut(&mut self, id: StringId) -> Option<&mut P> {
+        if self.tombstones.contains(&id) {
+            return None;
+        }
+        self.payloads.get_mut(id)
+    }
+
+    /// Iterates over the live (not removed) strings and their ids.
+    pub fn iter(&self) -> impl Iterator<Item = (StringId, &str)> {
+        self.entries().iter()
+    }
+
+    pub fn is_removed(&self, id: StringId) -> bool {
+        self.entries().is_removed(id)
+    }
+
+    /// Removes the string with the given id, returning `false` if it does not exist.
+    ///
+    /// The id is not reused: other ids stay valid until [`HashDb::compact`] is called.
+    pub fn remove(&mut self, id: StringId) -> bool {
+        if id >= self.strings.len() || self.tombstones.contains(&id) {
+            return false;
+        }
+
+        let features = std::mem::take(&mut self.string_features[id]);
+        self.unlink(id, &features);
+        self.strings[id] = String::new();
+        self.tombstones.insert(id);
+        true
+    }
+
+    /// Removes every live entry equal to `text`, returning how many were removed.
+    pub fn remove_str(&mut self, text: &str) -> usize {
+        let ids: Vec<StringId> = self
+            .iter()
+            .filter(|&(_, s)| s == text)
+            .map(|(id, _)| id)
+            .collect();
+        for &id in &ids {
+            self.remove(id);
+        }
+        ids.len()
+    }
+
+    /// Replaces the string with the given id, keeping its id.
+    /// Returns `false` if the id does not exist or was removed.
+    pub fn update(&mut self, id: StringId, new_text: String) -> bool {
+        if id >= self.strings.len() || self.tombstones.contains(&id) {
+            return false;
+        }
+
+        let old_features = std::mem::take(&mut self.string_features[id]);
+        self.unlink(id, &old_features);
+
+        let features = {
+            let mut interner = self.interner.lock().unwrap();
+            self.feature_extractor.features(&new_text, &mut interner)
+        };
+        self.link(id, &features);
+        self.strings[id] = new_text;
+        self.string_features[id] = features;
+        true
+    }
+
+    /// Drops removed entries and renumbers the remaining strings contiguously.
+    ///
+    /// Returns a table mapping every old id to its new id, or `None` if it was removed.
+    pub fn compact(&mut self) -> Vec<Option<StringId>> {
+        let mut remap = Vec::with_capacity(self.strings.len());
+        let mut next_id = 0;
+        for id in 0..self.strings.len() {
+            if self.tombstones.contains(&id) {
+                remap.push(None);
+            } else {
+                remap.push(Some(next_id));
+                next_id += 1;
+            }
+        }
+
+        if self.tombstones.is_empty() {
+            return remap;
+        }
+
+        let strings = std::mem::take(&mut self.strings);
+        let string_features = std::mem::take(&mut self.string_features);
+        let payloads = std::mem::take(&mut self.payloads);
+        let entries = strings.into_iter().zip(string_features).zip(payloads);
+        for (id, ((text, features), payload)) in entries.enumerate() {
+            if remap[id].is_some() {
+                self.strings.push(text);
+                self.string_features.push(features);
+                self.payloads.push(payload);
+            }
+        }
+
+        for size_map in self.feature_map.values_mut() {
+            for ids in size_map.values_mut() {
+                *ids = ids.iter().filter_map(|&id| remap[id]).collect();
+            }
+        }
+        self.tombstones.clear();
+        remap
+    }
+
+    fn link(&mut self, id: StringId, features: &[Spur]) {
+        let size_map = self.feature_map.entry(features.len()).or_default();
+        for &feature in features {
+            size_map.entry(feature).or_default().insert(id);
+        }
+    }
+
+    fn unlink(&mut self, id: StringId, features: &[Spur]) {
+        let size = features.len();
+        let Some(size_map) = self.feature_map.get_mut(&size) else {
+            return;
+        };
+        for feature in features {
+            if let Some(ids) = size_map.get_mut(feature) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    size_map.remove(feature);
+                }
+            }
+        }
+        // Drop empty buckets so `max_feature_len` stays accurate
+        if size_map.is_empty() {
+            self.feature_map.remove(&size);
+        }
+    }
+}
+
+impl<P: Serialize> HashDb<P> {
+    /// Saves the database to `path` in the crate's versioned binary format.
+    ///
+    /// Payloads are stored as JSON, so `P` must be serializable.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PersistError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), PersistError> {
+        persist::write_header(writer, &self.feature_extractor.descriptor())?;
+
+        let interner = self.interner.lock().unwrap();
+        persist::write_len(writer, interner.len())?;
+        for (_, feature) in interner.iter() {
+            persist::write_str(writer, feature)?;
+        }
+
+        persist::write_len(writer, self.strings.len())?;
+        for (text, features) in self.strings.iter().zip(&self.string_features) {
+            persist::write_str(writer, text)?;
+            persist::write_len(writer, features.len())?;
+            for &feature in features {
+                persist::write_spur(writer, feature)?;
+            }
+        }
+
+        persist::write_len(writer, self.tombstones.len())?;
+        for &id in &self.tombstones {
+            persist::write_len(writer, id)?;
+        }
+
+        // Zero-sized payloads carry no information, so they are not stored
+        if std::mem::size_of::<P>() == 0 {
+            persist::write_len(writer, 0)?;
+        } else {
+            persist::write_len(writer, self.payloads.len())?;
+            for payload in &self.payloads {
+                persist::write_payload(writer, payload)?;
+            }
+        }
+
+        persist::write_len(writer, self.feature_map.len())?;
+        for (&size, size_map) in &self.feature_map {
+            persist::write_len(writer, size)?;
+            persist::write_len(writer, size_map.len())?;
+            for (&feature, ids) in size_map {
+                persist::write_spur(writer, feature)?;
+                persist::write_len(writer, ids.len())?;
+                for &id in ids {
+                    persist::write_len(writer, id)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<P: DeserializeOwned> HashDb<P> {
+    /// Loads a database with payloads previously written with [`HashDb::save`].
+    pub fn load_with_payloads(
+        path: impl AsRef<Path>,
+        feature_extractor: Arc<dyn FeatureExtractor>,
+    ) -> Result<Self, PersistError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        Self::read_with_payloads(&mut reader, feature_extractor)
+    }
+
+    pub fn read_with_payloads<R: Read>(
+        reader: &mut R,
+        feature_extractor: Arc<dyn FeatureExtractor>,
+    ) -> Result<Self, PersistError> {
+        let descriptor = persist::read_header(reader)?;
+        let found = feature_extractor.descriptor();
+        if descriptor != found {
+            return Err(PersistError::ExtractorMismatch {
+                expected: descriptor,
+                found,
+            });
+        }
+
+        // Re-interning in key order reproduces the original keys.
+        let num_features = persist::read_len(reader)?;
+        let mut interner = Rodeo::default();
+        for index in 0..num_features {
+            let feature = persist::read_str(reader)?;
+            if interner.get_or_intern(feature).into_usize() != index {
+                return Err(PersistError::Corrupt(
+                    "duplicate feature in interner".to_string(),
+                ));
+            }
+        }
+
+        let num_strings = persist::read_len(reader)?;
+        // Lengths come from the file, so grow as records are read rather than preallocating
+        let mut strings = Vec::new();
+        let mut string_features = Vec::new();
+        for _ in 0..num_strings {
+            strings.push(persist::read_str(reader)?);
+            let len = persist::read_len(reader)?;
+            let features = (0..len)
+                .map(|_| persist::read_spur(reader, num_features))
+                .collect::<Result<Vec<_>, _>>()?;
+            string_features.push(features);
+        }
+
+        let mut tombstones = FxHashSet::default();
+        for _ in 0..persist::read_len(reader)? {
+            tombstones.insert(persist::read_string_id(reader, num_strings)?);
+        }
+
+        let num_payloads = persist::read_len(reader)?;
+        let payloads = if num_payloads == 0 {
+            // Zero-sized payloads are not stored, so only unit-like payloads can be loaded
+            (0..num_strings)
+                .map(|_| persist::unit_payload())
+                .collect::<Result<Vec<P>, _>>()?
+        } else if num_payloads == num_strings {
+            (0..num_strings)
+                .map(|_| persist::read_payload(reader))
+                .collect::<Result<Vec<P>, _>>()?
+        } else {
+            return Err(PersistError::Corrupt(format!(
+                "{num_payloads} payloads stored for {num_strings} strings"
+            )));
+        };
+
+        let num_sizes = persist::read_len(reader)?;
+        let mut feature_map: FeatureMap = FxHashMap::default();
+        for _ in 0..num_sizes {
+            let size = persist::read_len(reader)?;
+            let num_size_features = persist::read_len(reader)?;
+            let size_map = feature_map.entry(size).or_default();
+            for _ in 0..num_size_features {
+                let feature = persist::read_spur(reader, num_features)?;
+                let num_ids = persist::read_len(reader)?;
+                let ids = size_map.entry(feature).or_default();
+                for _ in 0..num_ids {
+                    ids.insert(persist::read_string_id(reader, num_strings)?);
+                }
+            }
+        }
+
+        Ok(Self::from_parts(
+            feature_extractor,
+            strings,
+            string_features,
+            feature_map,
+            interner,
+            tombstones,
+            payloads,
+        ))
+    }
+}
+
+impl<P: Send + Sync + 'static> Database for HashDb<P> {
     fn lookup_strings(&self, size: usize, feature: Spur) -> Option<&FxHashSet<StringId>> {
-        self.feature_map.get(&size)?.get(&feature)
+        self.entries().lookup_strings(size, feature)
     }
 
     fn get_string(&self, id: StringId) -> Option<&str> {
-        self.strings.get(id).map(AsRef::as_ref)
+        self.entries().get_string(id)
     }
 
     fn get_features(&self, id: StringId) -> Option<&Vec<Spur>> {
-        self.string_features.get(id)
+        self.entries().get_features(id)
     }
 
     fn feature_extractor(&self) -> &dyn FeatureExtractor {
@@ -92,14 +579,54 @@ impl Database for HashDb {
     }
 
     fn max_feature_len(&self) -> usize {
-        self.feature_map.keys().max().copied().unwrap_or(0)
+        self.entries().max_feature_len()
     }
 
-    fn interner(&self) -> Arc<Mutex<Rodeo>> {
-        Arc::clone(&self.interner)
+    fn total_strings(&self) -> usize {
+        self.entries().total_strings()
     }
 
-    fn total_strings(&self) -> usize {
-        self.strings.len()
+    fn string_id_bound(&self) -> usize {
+        self.entries().string_id_bound()
+    }
+
+    fn feature_id(&self, feature: &str) -> Option<Spur> {
+        self.interner.lock().unwrap().get(feature)
+    }
+
+    // Overridden so the interner is locked once per query rather than once per feature
+    fn query_features(&self, text: &str) -> Vec<Spur> {
+        let interner = self.interner.lock().unwrap();
+        self.feature_extractor
+            .query_features(text, &|feature| interner.get(feature))
+    }
+
+    fn batch_query_features(&self, texts: &[&str]) -> Vec<Vec<Spur>> {
+        let interner = self.interner.lock().unwrap();
+        texts
+            .iter()
+            .map(|text| {
+                self.feature_extractor
+                    .query_features(text, &|feature| interner.get(feature))
+            })
+            .collect()
+    }
+
+    fn get_payload(&self, id: StringId) -> Option<&dyn Any> {
+        self.payload(id).map(|payload| payload as &dyn Any)
+    }
+}
+
+impl<P: Default + Send + Sync + 'static> DatabaseMut for HashDb<P> {
+    fn insert(&mut self, text: String) {
+        HashDb::insert(self, text);
+    }
+
+    fn clear(&mut self) {
+        HashDb::clear(self);
+    }
+
+    fn interner(&self) -> Arc<Mutex<Rodeo>> {
+        HashDb::interner(self)
     }
 }
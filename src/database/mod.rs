@@ -1,22 +1,70 @@
+mod cdbpp;
+mod concurrent;
+mod entries;
+mod frozen;
 mod hashdb;
+mod persist;
+pub mod simstring_cpp;
+mod text;
 
 use crate::extractors::FeatureExtractor;
 use lasso::{Rodeo, Spur};
 use rustc_hash::FxHashSet;
+use std::any::Any;
 use std::sync::{Arc, Mutex};
 
 pub type StringId = usize;
 
+/// Read access to an indexed set of strings, as needed by [`Searcher`](crate::Searcher).
+///
+/// Searching only requires `&self`, so a database shared between threads can be searched
+/// concurrently; whether that takes a lock depends on the implementation.
 pub trait Database: Send + Sync {
-    fn insert(&mut self, text: String);
-    fn clear(&mut self);
     fn lookup_strings(&self, size: usize, feature: Spur) -> Option<&FxHashSet<StringId>>;
     fn get_string(&self, id: StringId) -> Option<&str>;
     fn get_features(&self, id: StringId) -> Option<&Vec<Spur>>;
     fn feature_extractor(&self) -> &dyn FeatureExtractor;
     fn max_feature_len(&self) -> usize;
-    fn interner(&self) -> Arc<Mutex<Rodeo>>;
     fn total_strings(&self) -> usize;
+
+    /// Returns the interned id of `feature`, or `None` if no indexed string has it.
+    fn feature_id(&self, feature: &str) -> Option<Spur>;
+
+    /// Extracts the features of a query without adding unseen features to the interner.
+    fn query_features(&self, text: &str) -> Vec<Spur> {
+        self.feature_extractor()
+            .query_features(text, &|feature| self.feature_id(feature))
+    }
+
+    /// Extracts the features of many queries, e.g. while taking a lock only once.
+    fn batch_query_features(&self, texts: &[&str]) -> Vec<Vec<Spur>> {
+        texts.iter().map(|text| self.query_features(text)).collect()
+    }
+
+    /// Exclusive upper bound of the ids in use, which exceeds `total_strings` when ids of
+    /// removed strings are kept reserved.
+    fn string_id_bound(&self) -> usize {
+        self.total_strings()
+    }
+
+    /// Returns the payload attached to a string, if the database stores payloads.
+    fn get_payload(&self, _id: StringId) -> Option<&dyn Any> {
+        None
+    }
+}
+
+/// A [`Database`] that strings can be added to in place.
+///
+/// Split from [`Database`] so read-only databases such as [`FrozenHashDb`] can be searched
+/// too; code that builds a database generically should bound on this trait.
+pub trait DatabaseMut: Database {
+    fn insert(&mut self, text: String);
+    fn clear(&mut self);
+    fn interner(&self) -> Arc<Mutex<Rodeo>>;
 }
 
+pub use concurrent::ConcurrentHashDb;
+pub use frozen::FrozenHashDb;
 pub use hashdb::HashDb;
+pub use persist::PersistError;
+pub use text::{MalformedLine, MalformedReason, TextOptions, TextReport};
@@ -0,0 +1,152 @@
+use super::StringId;
+use lasso::{Key, Spur};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{self, Read, Write};
+use thiserror::Error;
+
+/// Magic bytes identifying a serialized simstring_rust database.
+pub(crate) const MAGIC: &[u8; 8] = b"SSRUSTDB";
+/// Version of the on-disk layout written by this crate.
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum PersistError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Not a simstring_rust database file")]
+    InvalidMagic,
+    #[error(
+        "Unsupported database format version {0} (this build supports up to {FORMAT_VERSION})"
+    )]
+    UnsupportedVersion(u32),
+    #[error("Unsupported database: {0}")]
+    Unsupported(String),
+    #[error("Corrupt database file: {0}")]
+    Corrupt(String),
+    #[error("Invalid payload: {0}")]
+    Payload(#[from] serde_json::Error),
+    #[error("Feature extractor mismatch: database was built with `{expected}`, but `{found}` was supplied")]
+    ExtractorMismatch { expected: String, found: String },
+}
+
+pub(crate) fn write_u32<W: Write>(writer: &mut W, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_u64<W: Write>(writer: &mut W, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
+    write_u64(writer, len as u64)
+}
+
+pub(crate) fn write_str<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    write_len(writer, value.len())?;
+    writer.write_all(value.as_bytes())
+}
+
+pub(crate) fn write_spur<W: Write>(writer: &mut W, spur: Spur) -> io::Result<()> {
+    write_u32(writer, spur.into_usize() as u32)
+}
+
+pub(crate) fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub(crate) fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+pub(crate) fn read_len<R: Read>(reader: &mut R) -> Result<usize, PersistError> {
+    let len = read_u64(reader)?;
+    usize::try_from(len).map_err(|_| PersistError::Corrupt(format!("length {len} is too large")))
+}
+
+pub(crate) fn read_str<R: Read>(reader: &mut R) -> Result<String, PersistError> {
+    let len = read_len(reader)?;
+    let mut buf = Vec::new();
+    reader.by_ref().take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    String::from_utf8(buf).map_err(|e| PersistError::Corrupt(format!("invalid UTF-8: {e}")))
+}
+
+/// Reads a string id, checking that it refers to one of the `num_strings` stored strings.
+pub(crate) fn read_string_id<R: Read>(
+    reader: &mut R,
+    num_strings: usize,
+) -> Result<StringId, PersistError> {
+    let id = read_len(reader)?;
+    if id >= num_strings {
+        return Err(PersistError::Corrupt(format!(
+            "string id {id} out of range ({num_strings} strings)"
+        )));
+    }
+    Ok(id)
+}
+
+/// Reads a feature key, checking that it refers to one of the `num_features` interned features.
+pub(crate) fn read_spur<R: Read>(
+    reader: &mut R,
+    num_features: usize,
+) -> Result<Spur, PersistError> {
+    let index = read_u32(reader)? as usize;
+    if index >= num_features {
+        return Err(PersistError::Corrupt(format!(
+            "feature key {index} out of range ({num_features} features interned)"
+        )));
+    }
+    Spur::try_from_usize(index)
+        .ok_or_else(|| PersistError::Corrupt(format!("invalid feature key {index}")))
+}
+
+pub(crate) fn write_payload<W: Write, P: Serialize>(
+    writer: &mut W,
+    payload: &P,
+) -> Result<(), PersistError> {
+    let json = serde_json::to_vec(payload)?;
+    write_len(writer, json.len())?;
+    writer.write_all(&json)?;
+    Ok(())
+}
+
+pub(crate) fn read_payload<R: Read, P: DeserializeOwned>(
+    reader: &mut R,
+) -> Result<P, PersistError> {
+    Ok(serde_json::from_str(&read_str(reader)?)?)
+}
+
+/// Produces the payload of a database stored without payloads, which only unit-like
+/// payload types (such as `()`) can represent.
+pub(crate) fn unit_payload<P: DeserializeOwned>() -> Result<P, PersistError> {
+    Ok(serde_json::from_str("null")?)
+}
+
+pub(crate) fn write_header<W: Write>(writer: &mut W, descriptor: &str) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    write_u32(writer, FORMAT_VERSION)?;
+    write_str(writer, descriptor)
+}
+
+/// Reads the file header, returning the recorded extractor descriptor.
+pub(crate) fn read_header<R: Read>(reader: &mut R) -> Result<String, PersistError> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(PersistError::InvalidMagic);
+    }
+
+    let version = read_u32(reader)?;
+    if version != FORMAT_VERSION {
+        return Err(PersistError::UnsupportedVersion(version));
+    }
+
+    read_str(reader)
+}
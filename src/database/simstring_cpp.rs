@@ -0,0 +1,265 @@
+//! Interoperability with databases built by the C++ `simstring` tool.
+//!
+//! A C++ database consists of a master file (e.g. `names.db`) holding every indexed string,
+//! and one cdb++ file per feature-set size (`names.db.<size>.cdb`) mapping each n-gram to the
+//! master file offsets of the strings containing it.
+
+use super::cdbpp::{self, BYTEORDER_CHECK};
+use super::persist::PersistError;
+use super::{HashDb, StringId};
+use crate::extractors::SimstringNgrams;
+use lasso::{Rodeo, Spur};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const MAGIC: &[u8; 4] = b"SSDB";
+const STREAM_VERSION: u32 = 2;
+const HEADER_SIZE: usize = 36;
+
+struct Header {
+    char_size: u32,
+    n: u32,
+    begin_end_marks: bool,
+    num_entries: u32,
+    max_size: u32,
+}
+
+fn index_path(path: &Path, size: usize) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".{size}.cdb"));
+    PathBuf::from(name)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, PersistError> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| PersistError::Corrupt("truncated simstring master file".to_string()))
+}
+
+fn read_header(data: &[u8]) -> Result<Header, PersistError> {
+    if data.len() < HEADER_SIZE || &data[..4] != MAGIC {
+        return Err(PersistError::InvalidMagic);
+    }
+    if read_u32(data, 4)? != BYTEORDER_CHECK {
+        return Err(PersistError::Unsupported(
+            "simstring database has a foreign byte order".to_string(),
+        ));
+    }
+    let version = read_u32(data, 8)?;
+    if version == 0 || version > STREAM_VERSION {
+        return Err(PersistError::UnsupportedVersion(version));
+    }
+
+    let header = Header {
+        char_size: read_u32(data, 16)?,
+        n: read_u32(data, 20)?,
+        begin_end_marks: read_u32(data, 24)? != 0,
+        num_entries: read_u32(data, 28)?,
+        max_size: read_u32(data, 32)?,
+    };
+    if header.char_size != 1 && header.char_size != 4 {
+        return Err(PersistError::Unsupported(format!(
+            "character size {} (only 1-byte and 4-byte databases are supported)",
+            header.char_size
+        )));
+    }
+    // Every entry takes at least its terminating code unit
+    let max_entries = (data.len() - HEADER_SIZE) / header.char_size as usize;
+    if header.num_entries as usize > max_entries {
+        return Err(PersistError::Corrupt(format!(
+            "header claims {} entries, but the file holds at most {max_entries}",
+            header.num_entries
+        )));
+    }
+    Ok(header)
+}
+
+/// Decodes a run of `char_size`-wide code units into a string.
+///
+/// 1-byte keys are decoded byte by byte, since n-gram keys may split multi-byte sequences.
+/// 1-byte strings must be valid UTF-8.
+fn decode_units(bytes: &[u8], char_size: u32, is_key: bool) -> Result<String, PersistError> {
+    if char_size == 1 {
+        if is_key {
+            Ok(bytes.iter().copied().map(char::from).collect())
+        } else {
+            String::from_utf8(bytes.to_vec())
+                .map_err(|_| PersistError::Corrupt("string is not valid UTF-8".to_string()))
+        }
+    } else {
+        bytes
+            .chunks_exact(4)
+            .map(|unit| {
+                let code = u32::from_le_bytes([unit[0], unit[1], unit[2], unit[3]]);
+                char::from_u32(code)
+                    .ok_or_else(|| PersistError::Corrupt(format!("invalid code point {code:#x}")))
+            })
+            .collect()
+    }
+}
+
+fn encode_units(text: &str, unicode: bool, bytes_as_chars: bool, out: &mut Vec<u8>) {
+    if unicode {
+        for ch in text.chars() {
+            out.extend_from_slice(&(ch as u32).to_le_bytes());
+        }
+    } else if bytes_as_chars {
+        out.extend(text.chars().map(|ch| ch as u8));
+    } else {
+        out.extend_from_slice(text.as_bytes());
+    }
+}
+
+/// Opens a database written by the C++ `simstring` tool.
+///
+/// The returned [`HashDb`] is configured with a [`SimstringNgrams`] extractor matching the
+/// database header, so queries are featurized exactly like the C++ tool would.
+pub fn read<P: AsRef<Path>>(path: P) -> Result<HashDb, PersistError> {
+    let path = path.as_ref();
+    let data = fs::read(path)?;
+    let header = read_header(&data)?;
+    let unit = header.char_size as usize;
+
+    let mut strings = Vec::with_capacity(header.num_entries as usize);
+    let mut offsets: FxHashMap<u32, StringId> = FxHashMap::default();
+    let mut pos = HEADER_SIZE;
+    while pos + unit <= data.len() {
+        let len = data[pos..]
+            .chunks_exact(unit)
+            .position(|c| c.iter().all(|&b| b == 0))
+            .ok_or_else(|| PersistError::Corrupt("unterminated string".to_string()))?;
+        let bytes = &data[pos..pos + len * unit];
+        offsets.insert(pos as u32, strings.len());
+        let string = decode_units(bytes, header.char_size, false).map_err(|e| match e {
+            PersistError::Corrupt(reason) => {
+                PersistError::Corrupt(format!("entry {} at offset {pos}: {reason}", strings.len()))
+            }
+            e => e,
+        })?;
+        strings.push(string);
+        pos += (len + 1) * unit;
+    }
+
+    let mut interner = Rodeo::default();
+    let mut string_features: Vec<Vec<Spur>> = vec![Vec::new(); strings.len()];
+    let mut feature_map: FxHashMap<usize, FxHashMap<Spur, FxHashSet<StringId>>> =
+        FxHashMap::default();
+    // The C++ tool stores the number of size slots (largest size + 1) and reads sizes below
+    // it. Older exports of this crate stored the largest size itself, so it is read too.
+    for size in 1..=header.max_size as usize {
+        let index = match fs::read(index_path(path, size)) {
+            Ok(index) => index,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let size_map = feature_map.entry(size).or_default();
+        for (key, value) in cdbpp::read_records(&index)? {
+            let feature = interner.get_or_intern(decode_units(key, header.char_size, true)?);
+            let ids = size_map.entry(feature).or_default();
+            for offset in value.chunks_exact(4) {
+                let offset = u32::from_le_bytes([offset[0], offset[1], offset[2], offset[3]]);
+                let id = *offsets.get(&offset).ok_or_else(|| {
+                    PersistError::Corrupt(format!("index refers to unknown offset {offset}"))
+                })?;
+                ids.insert(id);
+                string_features[id].push(feature);
+            }
+        }
+    }
+    for features in &mut string_features {
+        features.sort_unstable();
+    }
+
+    let extractor = SimstringNgrams::new(
+        header.n as usize,
+        header.begin_end_marks,
+        header.char_size == 4,
+    );
+    let payloads = vec![(); strings.len()];
+    Ok(HashDb::from_parts(
+        Arc::new(extractor),
+        strings,
+        string_features,
+        feature_map,
+        interner,
+        FxHashSet::default(),
+        payloads,
+    ))
+}
+
+/// Writes `strings` into the C++ `simstring` layout at `path`.
+///
+/// The n-gram indexes are built with `ngrams`, since the C++ tool can only query features it
+/// generates itself. To export a [`HashDb`], pass `db.iter().map(|(_, text)| text)`.
+pub fn write<I, S>(
+    strings: I,
+    path: impl AsRef<Path>,
+    ngrams: &SimstringNgrams,
+) -> Result<(), PersistError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let path = path.as_ref();
+    let mut master = vec![0u8; HEADER_SIZE];
+    let mut indexes: BTreeMap<usize, BTreeMap<String, Vec<u32>>> = BTreeMap::new();
+    let terminator = if ngrams.unicode() { 4 } else { 1 };
+
+    let mut num_entries = 0u32;
+    for text in strings {
+        let text = text.as_ref();
+        num_entries += 1;
+        let offset = u32::try_from(master.len())
+            .map_err(|_| PersistError::Unsupported("master file exceeds 4 GiB".to_string()))?;
+        encode_units(text, ngrams.unicode(), false, &mut master);
+        master.extend(std::iter::repeat_n(0u8, terminator));
+
+        let features = ngrams.ngrams(text);
+        let size_index = indexes.entry(features.len()).or_default();
+        for feature in features {
+            size_index.entry(feature).or_default().push(offset);
+        }
+    }
+
+    // Like the C++ writer, store the number of size slots, one more than the largest size
+    let max_size = indexes.keys().next_back().map_or(0, |&size| size + 1);
+    let header = [
+        BYTEORDER_CHECK,
+        STREAM_VERSION,
+        master.len() as u32,
+        terminator as u32,
+        ngrams.n() as u32,
+        ngrams.begin_end_marks() as u32,
+        num_entries,
+        max_size as u32,
+    ];
+    master[..4].copy_from_slice(MAGIC);
+    for (i, value) in header.iter().enumerate() {
+        master[4 + i * 4..8 + i * 4].copy_from_slice(&value.to_le_bytes());
+    }
+    fs::write(path, &master)?;
+
+    for (size, size_index) in indexes {
+        let records: Vec<(Vec<u8>, Vec<u8>)> = size_index
+            .into_iter()
+            .map(|(feature, offsets)| {
+                let mut key = Vec::new();
+                encode_units(&feature, ngrams.unicode(), true, &mut key);
+                let value = offsets.iter().flat_map(|o| o.to_le_bytes()).collect();
+                (key, value)
+            })
+            .collect();
+
+        let mut writer = BufWriter::new(fs::File::create(index_path(path, size))?);
+        cdbpp::write_records(&mut writer, &records)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
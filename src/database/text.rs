@@ -0,0 +1,167 @@
+//! Building databases from newline-delimited text files.
+
+use super::HashDb;
+use crate::extractors::FeatureExtractor;
+use flate2::read::MultiGzDecoder;
+use rustc_hash::FxHashSet;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// How lines are turned into database entries. Gzip-compressed input is detected
+/// automatically.
+#[derive(Debug, Clone, Default)]
+pub struct TextOptions {
+    /// Strip leading and trailing whitespace from each entry.
+    pub trim: bool,
+    /// Skip lines that are empty (after trimming, if enabled).
+    pub skip_blank: bool,
+    /// Insert only the first occurrence of each string.
+    pub dedup: bool,
+}
+
+/// Why a line was not inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MalformedReason {
+    InvalidUtf8,
+    /// A TSV line without a tab separating the string from its payload.
+    MissingPayload,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedLine {
+    /// 1-based line number in the (decompressed) input.
+    pub line_number: usize,
+    pub reason: MalformedReason,
+}
+
+/// Summary of a text import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextReport {
+    pub inserted: usize,
+    pub skipped_blank: usize,
+    pub skipped_duplicates: usize,
+    pub malformed: Vec<MalformedLine>,
+}
+
+fn open_text<'a, R: Read + 'a>(reader: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    let mut reader = BufReader::new(reader);
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Reads `reader` line by line, inserting each entry split off by `split_line`.
+fn read_lines<P, R: Read>(
+    db: &mut HashDb<P>,
+    reader: R,
+    options: &TextOptions,
+    split_line: impl Fn(&str) -> Option<(&str, P)>,
+) -> io::Result<TextReport> {
+    let mut reader = open_text(reader)?;
+    let mut report = TextReport::default();
+    let mut seen: FxHashSet<String> = FxHashSet::default();
+    let mut buf = Vec::new();
+
+    let mut line_number = 0;
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        line_number += 1;
+        if buf.ends_with(b"\n") {
+            buf.pop();
+            if buf.ends_with(b"\r") {
+                buf.pop();
+            }
+        }
+
+        let Ok(line) = std::str::from_utf8(&buf) else {
+            report.malformed.push(MalformedLine {
+                line_number,
+                reason: MalformedReason::InvalidUtf8,
+            });
+            continue;
+        };
+        let Some((text, payload)) = split_line(line) else {
+            report.malformed.push(MalformedLine {
+                line_number,
+                reason: MalformedReason::MissingPayload,
+            });
+            continue;
+        };
+
+        let text = if options.trim { text.trim() } else { text };
+        if options.skip_blank && text.is_empty() {
+            report.skipped_blank += 1;
+            continue;
+        }
+        if options.dedup && !seen.insert(text.to_string()) {
+            report.skipped_duplicates += 1;
+            continue;
+        }
+
+        db.insert_with_payload(text.to_string(), payload);
+        report.inserted += 1;
+    }
+
+    Ok(report)
+}
+
+impl HashDb {
+    /// Builds a database with one entry per line of `reader`.
+    ///
+    /// Lines that are not valid UTF-8 are skipped and listed in the returned [`TextReport`].
+    pub fn from_reader<R: Read>(
+        reader: R,
+        feature_extractor: Arc<dyn FeatureExtractor>,
+        options: &TextOptions,
+    ) -> io::Result<(Self, TextReport)> {
+        let mut db = Self::new(feature_extractor);
+        let report = read_lines(&mut db, reader, options, |line| Some((line, ())))?;
+        Ok((db, report))
+    }
+
+    /// Like [`HashDb::from_reader`], reading the file at `path`.
+    pub fn from_path(
+        path: impl AsRef<Path>,
+        feature_extractor: Arc<dyn FeatureExtractor>,
+        options: &TextOptions,
+    ) -> io::Result<(Self, TextReport)> {
+        Self::from_reader(File::open(path)?, feature_extractor, options)
+    }
+}
+
+impl HashDb<String> {
+    /// Builds a database from `string<TAB>payload` lines.
+    ///
+    /// The payload is everything after the first tab. Lines without a tab are skipped and
+    /// listed in the returned [`TextReport`], as are lines that are not valid UTF-8.
+    pub fn from_tsv_reader<R: Read>(
+        reader: R,
+        feature_extractor: Arc<dyn FeatureExtractor>,
+        options: &TextOptions,
+    ) -> io::Result<(Self, TextReport)> {
+        let mut db = Self::with_payloads(feature_extractor);
+        let report = read_lines(&mut db, reader, options, |line| {
+            line.split_once('\t')
+                .map(|(text, payload)| (text, payload.to_string()))
+        })?;
+        Ok((db, report))
+    }
+
+    /// Like [`HashDb::from_tsv_reader`], reading the file at `path`.
+    pub fn from_tsv_path(
+        path: impl AsRef<Path>,
+        feature_extractor: Arc<dyn FeatureExtractor>,
+        options: &TextOptions,
+    ) -> io::Result<(Self, TextReport)> {
+        Self::from_tsv_reader(File::open(path)?, feature_extractor, options)
+    }
+}
@@ -0,0 +1,165 @@
+//! Character-level edit distances, computed over `char`s rather than bytes.
+
+use rustc_hash::FxHashMap;
+
+/// Number of single-character insertions, deletions and substitutions turning `a` into `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() || b.is_empty() {
+        return a.len().max(b.len());
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, &ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// [`levenshtein`] scaled to a similarity in `[0, 1]`, where 1 means equal strings.
+pub fn levenshtein_similarity(a: &str, b: &str) -> f64 {
+    let max_len = a.chars().count().max(b.chars().count());
+    if max_len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / max_len as f64
+}
+
+/// Like [`levenshtein`], but also counting a transposition of two characters as one edit.
+///
+/// This is the unrestricted Damerau-Levenshtein distance, so substrings may be edited more
+/// than once (`"ca"` to `"abc"` takes two edits).
+pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() || b.is_empty() {
+        return a.len().max(b.len());
+    }
+
+    // Lowrance-Wagner: the table has an extra border row and column holding `max`
+    let max = a.len() + b.len();
+    let width = b.len() + 2;
+    let mut d = vec![0; (a.len() + 2) * width];
+    d[0] = max;
+    for i in 0..=a.len() {
+        d[(i + 1) * width] = max;
+        d[(i + 1) * width + 1] = i;
+    }
+    for j in 0..=b.len() {
+        d[j + 1] = max;
+        d[width + j + 1] = j;
+    }
+
+    // Row of the last occurrence of each character of `a`
+    let mut last_row: FxHashMap<char, usize> = FxHashMap::default();
+    for i in 1..=a.len() {
+        // Column of the last match in this row
+        let mut last_col = 0;
+        for j in 1..=b.len() {
+            let k = last_row.get(&b[j - 1]).copied().unwrap_or(0);
+            let l = last_col;
+            let cost = if a[i - 1] == b[j - 1] {
+                last_col = j;
+                0
+            } else {
+                1
+            };
+            let substitution = d[i * width + j] + cost;
+            let insertion = d[(i + 1) * width + j] + 1;
+            let deletion = d[i * width + j + 1] + 1;
+            let transposition = d[k * width + l] + (i - k - 1) + 1 + (j - l - 1);
+            d[(i + 1) * width + j + 1] =
+                substitution.min(insertion).min(deletion).min(transposition);
+        }
+        last_row.insert(a[i - 1], i);
+    }
+    d[(a.len() + 1) * width + b.len() + 1]
+}
+
+/// [`damerau_levenshtein`] scaled to a similarity in `[0, 1]`, where 1 means equal strings.
+pub fn damerau_levenshtein_similarity(a: &str, b: &str) -> f64 {
+    let max_len = a.chars().count().max(b.chars().count());
+    if max_len == 0 {
+        return 1.0;
+    }
+    1.0 - damerau_levenshtein(a, b) as f64 / max_len as f64
+}
+
+/// Jaro similarity in `[0, 1]`, based on the characters the strings share within a window
+/// and how many of them are out of order.
+pub fn jaro(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0;
+    for (i, &ca) in a.iter().enumerate() {
+        let lo = i.saturating_sub(window);
+        let hi = (i + window + 1).min(b.len());
+        for j in lo..hi {
+            if !b_matched[j] && b[j] == ca {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let a_order = a.iter().zip(&a_matched).filter(|(_, &m)| m).map(|(c, _)| c);
+    let b_order = b.iter().zip(&b_matched).filter(|(_, &m)| m).map(|(c, _)| c);
+    let transpositions = a_order.zip(b_order).filter(|(x, y)| x != y).count() / 2;
+
+    let m = matches as f64;
+    (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0
+}
+
+/// [`jaro`] boosted for strings sharing a prefix of up to four characters, with the usual
+/// scaling factor of 0.1.
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let similarity = jaro(a, b);
+    let prefix = a
+        .chars()
+        .zip(b.chars())
+        .take(4)
+        .take_while(|(x, y)| x == y)
+        .count();
+    similarity + prefix as f64 * 0.1 * (1.0 - similarity)
+}
+
+/// A character-level similarity used to verify or re-rank n-gram matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditMeasure {
+    Levenshtein,
+    DamerauLevenshtein,
+    JaroWinkler,
+}
+
+impl EditMeasure {
+    /// Similarity of `a` and `b` in `[0, 1]`, where 1 means equal strings.
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        match self {
+            EditMeasure::Levenshtein => levenshtein_similarity(a, b),
+            EditMeasure::DamerauLevenshtein => damerau_levenshtein_similarity(a, b),
+            EditMeasure::JaroWinkler => jaro_winkler(a, b),
+        }
+    }
+}
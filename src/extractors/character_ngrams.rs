@@ -1,5 +1,8 @@
-use crate::FeatureExtractor;
+use super::parse_debug_str;
+use crate::{CompositeExtractor, FeatureExtractor};
 use lasso::{Rodeo, Spur};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct CharacterNgrams {
@@ -14,6 +17,29 @@ impl CharacterNgrams {
             endmarker: endmarker.to_string(),
         }
     }
+
+    /// Rebuilds the extractor a [`descriptor`](FeatureExtractor::descriptor) was produced by,
+    /// e.g. the one recorded in a saved database. Returns `None` for any other descriptor.
+    pub fn from_descriptor(descriptor: &str) -> Option<Self> {
+        let rest = descriptor.strip_prefix("CharacterNgrams(n=")?;
+        let (n, rest) = rest.split_once(", endmarker=")?;
+        let (endmarker, rest) = parse_debug_str(rest)?;
+        if rest != ")" {
+            return None;
+        }
+        Some(Self {
+            n: n.parse().ok()?,
+            endmarker,
+        })
+    }
+
+    /// Combines n-grams of every size in `ns`, padded with the default `"$"` endmarker.
+    pub fn range(ns: RangeInclusive<usize>) -> CompositeExtractor {
+        CompositeExtractor::new(
+            ns.map(|n| Arc::new(Self::new(n, "$")) as Arc<dyn FeatureExtractor>)
+                .collect(),
+        )
+    }
 }
 
 impl Default for CharacterNgrams {
@@ -60,4 +86,11 @@ impl FeatureExtractor for CharacterNgrams {
 
         super::append_feature_counts(interner, ngrams)
     }
+
+    fn descriptor(&self) -> String {
+        format!(
+            "CharacterNgrams(n={}, endmarker={:?})",
+            self.n, self.endmarker
+        )
+    }
 }
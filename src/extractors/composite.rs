@@ -0,0 +1,58 @@
+use crate::FeatureExtractor;
+use lasso::{Rodeo, Spur};
+use std::sync::Arc;
+
+/// Combines the features of several extractors, e.g. character unigrams, bigrams and
+/// trigrams, or character trigrams and word unigrams.
+///
+/// Each child's features are prefixed with the child's position (`"0|ab1"`, `"1|ab1"`), so
+/// the same string produced by two children counts as two different features.
+#[derive(Clone)]
+pub struct CompositeExtractor {
+    children: Vec<Arc<dyn FeatureExtractor>>,
+}
+
+impl CompositeExtractor {
+    pub fn new(children: Vec<Arc<dyn FeatureExtractor>>) -> Self {
+        Self { children }
+    }
+
+    pub fn children(&self) -> &[Arc<dyn FeatureExtractor>] {
+        &self.children
+    }
+}
+
+fn namespaced(child: usize, feature: &str) -> String {
+    format!("{child}|{feature}")
+}
+
+impl FeatureExtractor for CompositeExtractor {
+    fn features(&self, text: &str, interner: &mut Rodeo) -> Vec<Spur> {
+        let mut features = Vec::new();
+        for (i, child) in self.children.iter().enumerate() {
+            // Children intern into a scratch interner, so only namespaced features reach
+            // the database's
+            let mut scratch = Rodeo::default();
+            for feature in child.features(text, &mut scratch) {
+                features.push(interner.get_or_intern(namespaced(i, scratch.resolve(&feature))));
+            }
+        }
+        features.sort_unstable();
+        features
+    }
+
+    fn query_features(&self, text: &str, lookup: &dyn Fn(&str) -> Option<Spur>) -> Vec<Spur> {
+        let mut features = Vec::new();
+        for (i, child) in self.children.iter().enumerate() {
+            let child_lookup = |feature: &str| lookup(&namespaced(i, feature));
+            features.extend(child.query_features(text, &child_lookup));
+        }
+        features.sort_unstable();
+        features
+    }
+
+    fn descriptor(&self) -> String {
+        let children: Vec<String> = self.children.iter().map(|c| c.descriptor()).collect();
+        format!("CompositeExtractor([{}])", children.join(", "))
+    }
+}
@@ -0,0 +1,60 @@
+use crate::FeatureExtractor;
+use lasso::{Rodeo, Spur};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Like [`CharacterNgrams`](crate::CharacterNgrams), but windows over extended grapheme
+/// clusters instead of `char`s.
+///
+/// Emoji with skin tone modifiers, ZWJ sequences, flags and letters with combining marks
+/// (as in Devanagari or Thai) count as a single unit, so they are never split across n-grams.
+/// Each copy of the endmarker padding the text is one unit as well.
+#[derive(Clone)]
+pub struct GraphemeNgrams {
+    n: usize,
+    endmarker: String,
+}
+
+impl GraphemeNgrams {
+    pub fn new(n: usize, endmarker: &str) -> Self {
+        Self {
+            n,
+            endmarker: endmarker.to_string(),
+        }
+    }
+}
+
+impl Default for GraphemeNgrams {
+    fn default() -> Self {
+        Self::new(2, "$")
+    }
+}
+
+impl FeatureExtractor for GraphemeNgrams {
+    fn features(&self, text: &str, interner: &mut Rodeo) -> Vec<Spur> {
+        if self.n == 0 {
+            return vec![];
+        }
+
+        let padding_len = self.n.saturating_sub(1);
+        let padding = std::iter::repeat_n(self.endmarker.as_str(), padding_len);
+        let units: Vec<&str> = padding
+            .clone()
+            .chain(text.graphemes(true))
+            .chain(padding)
+            .collect();
+
+        let ngrams: Vec<String> = units
+            .windows(self.n)
+            .map(|window| window.concat())
+            .collect();
+
+        super::append_feature_counts(interner, ngrams)
+    }
+
+    fn descriptor(&self) -> String {
+        format!(
+            "GraphemeNgrams(n={}, endmarker={:?})",
+            self.n, self.endmarker
+        )
+    }
+}
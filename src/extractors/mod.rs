@@ -1,9 +1,17 @@
 mod character_ngrams;
+mod composite;
+mod grapheme_ngrams;
+mod normalizer;
+mod phonetic;
+mod positional_qgrams;
+mod simstring_ngrams;
+mod skip_grams;
 mod word_ngrams;
 
-use lasso::{Rodeo, Spur};
+use lasso::{Key, Rodeo, Spur};
 use rustc_hash::FxHashMap;
 use std::fmt::Write;
+use std::sync::Arc;
 
 /// Takes a list of features and makes each one unique by appending its occurrence count,
 /// then interns the result and returns them sorted.
@@ -26,10 +34,89 @@ pub(crate) fn append_feature_counts(interner: &mut Rodeo, features: Vec<String>)
     unique_features
 }
 
+/// Parses a string literal written with `{:?}`, returning it and the text after it.
+pub(crate) fn parse_debug_str(text: &str) -> Option<(String, &str)> {
+    let mut chars = text.strip_prefix('"')?.chars();
+    let mut parsed = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some((parsed, chars.as_str())),
+            '\\' => parsed.push(match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                'u' => {
+                    let rest = chars.as_str().strip_prefix('{')?;
+                    let (hex, _) = rest.split_once('}')?;
+                    for _ in 0..hex.len() + 2 {
+                        chars.next();
+                    }
+                    char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+                }
+                escaped => escaped,
+            }),
+            c => parsed.push(c),
+        }
+    }
+    None
+}
+
+/// Id given to query features that were never interned by the database. It is the largest key
+/// a `Spur` can hold, which an interner would only hand out after ~4 billion features, so it
+/// never matches an indexed feature.
+pub(crate) fn unseen_feature() -> Spur {
+    Spur::try_from_usize(u32::MAX as usize - 1).unwrap()
+}
+
 pub trait FeatureExtractor: Send + Sync {
     /// Extracts features from text, interning them and returning their IDs.
     fn features(&self, text: &str, interner: &mut Rodeo) -> Vec<Spur>;
+
+    /// Extracts features from a query without interning them. Features `lookup` does not know
+    /// are mapped to a sentinel id that never matches, but still count towards the query size.
+    ///
+    /// The default implementation extracts into a scratch interner and translates the result.
+    fn query_features(&self, text: &str, lookup: &dyn Fn(&str) -> Option<Spur>) -> Vec<Spur> {
+        let mut scratch = Rodeo::default();
+        let mut features: Vec<Spur> = self
+            .features(text, &mut scratch)
+            .into_iter()
+            .map(|feature| lookup(scratch.resolve(&feature)).unwrap_or_else(unseen_feature))
+            .collect();
+        features.sort_unstable();
+        features
+    }
+
+    /// Describes the extractor and its configuration. Databases record this so they can
+    /// refuse to be used with a differently configured extractor.
+    fn descriptor(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+}
+
+/// Lets a shared extractor, including an `Arc<dyn FeatureExtractor>` chosen at runtime, be
+/// wrapped by generic extractors such as [`Normalizer`].
+impl<T: FeatureExtractor + ?Sized> FeatureExtractor for Arc<T> {
+    fn features(&self, text: &str, interner: &mut Rodeo) -> Vec<Spur> {
+        (**self).features(text, interner)
+    }
+
+    fn query_features(&self, text: &str, lookup: &dyn Fn(&str) -> Option<Spur>) -> Vec<Spur> {
+        (**self).query_features(text, lookup)
+    }
+
+    fn descriptor(&self) -> String {
+        (**self).descriptor()
+    }
 }
 
 pub use character_ngrams::CharacterNgrams;
+pub use composite::CompositeExtractor;
+pub use grapheme_ngrams::GraphemeNgrams;
+pub use normalizer::{Normalization, Normalizer};
+pub use phonetic::{PhoneticAlgorithm, PhoneticExtractor};
+pub use positional_qgrams::PositionalQgrams;
+pub use simstring_ngrams::SimstringNgrams;
+pub use skip_grams::SkipGrams;
 pub use word_ngrams::WordNgrams;
@@ -0,0 +1,115 @@
+use crate::FeatureExtractor;
+use caseless::default_case_fold_str;
+use lasso::{Rodeo, Spur};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// A single text transformation applied by a [`Normalizer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    /// Unicode lowercase mapping ([`str::to_lowercase`]). Unlike [`Normalization::CaseFold`],
+    /// it keeps characters without a lowercase form, so "STRASSE" and "straße" still differ.
+    Lowercase,
+    /// Full Unicode case folding, which also maps "ß" to "ss", "ﬁ" to "fi" and final sigma to
+    /// sigma, so strings differing only in case compare equal.
+    CaseFold,
+    /// Canonical composition, so precomposed and decomposed accents compare equal.
+    Nfc,
+    /// Compatibility composition, which also folds ligatures, full-width forms and the like.
+    Nfkc,
+    /// Removes combining marks after canonical decomposition, turning "é" into "e".
+    StripDiacritics,
+    /// Trims the text and replaces every run of whitespace with a single space.
+    CollapseWhitespace,
+    /// Removes characters that are neither alphanumeric, whitespace nor combining marks.
+    RemovePunctuation,
+}
+
+impl Normalization {
+    fn apply(&self, text: &str) -> String {
+        match self {
+            Normalization::Lowercase => text.to_lowercase(),
+            Normalization::CaseFold => default_case_fold_str(text),
+            Normalization::Nfc => text.nfc().collect(),
+            Normalization::Nfkc => text.nfkc().collect(),
+            Normalization::StripDiacritics => text
+                .nfd()
+                .filter(|&ch| !is_combining_mark(ch))
+                .nfc()
+                .collect(),
+            Normalization::CollapseWhitespace => {
+                text.split_whitespace().collect::<Vec<_>>().join(" ")
+            }
+            Normalization::RemovePunctuation => text
+                .chars()
+                .filter(|&ch| ch.is_alphanumeric() || ch.is_whitespace() || is_combining_mark(ch))
+                .collect(),
+        }
+    }
+}
+
+/// Wraps a feature extractor, normalizing text before features are extracted from it.
+///
+/// The same steps run on inserted strings and on queries, so "Café", "CAFE" and a decomposed
+/// "café" can all share features. The steps are part of the [`descriptor`], which databases
+/// record when saved, so a database cannot be loaded with a differently normalizing extractor.
+///
+/// [`descriptor`]: FeatureExtractor::descriptor
+#[derive(Clone)]
+pub struct Normalizer<E: FeatureExtractor> {
+    inner: E,
+    steps: Vec<Normalization>,
+}
+
+impl<E: FeatureExtractor> Normalizer<E> {
+    /// Applies `steps` in order before handing the text to `inner`.
+    pub fn new(inner: E, steps: Vec<Normalization>) -> Self {
+        Self { inner, steps }
+    }
+
+    /// Composes to NFKC, folds case, strips diacritics and collapses whitespace.
+    pub fn standard(inner: E) -> Self {
+        Self::new(
+            inner,
+            vec![
+                Normalization::Nfkc,
+                Normalization::CaseFold,
+                Normalization::StripDiacritics,
+                Normalization::CollapseWhitespace,
+            ],
+        )
+    }
+
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    pub fn steps(&self) -> &[Normalization] {
+        &self.steps
+    }
+
+    /// Returns `text` after every normalization step.
+    pub fn normalize(&self, text: &str) -> String {
+        self.steps
+            .iter()
+            .fold(text.to_string(), |text, step| step.apply(&text))
+    }
+}
+
+impl<E: FeatureExtractor> FeatureExtractor for Normalizer<E> {
+    fn features(&self, text: &str, interner: &mut Rodeo) -> Vec<Spur> {
+        self.inner.features(&self.normalize(text), interner)
+    }
+
+    fn query_features(&self, text: &str, lookup: &dyn Fn(&str) -> Option<Spur>) -> Vec<Spur> {
+        self.inner.query_features(&self.normalize(text), lookup)
+    }
+
+    fn descriptor(&self) -> String {
+        format!(
+            "Normalizer(steps={:?}, {})",
+            self.steps,
+            self.inner.descriptor()
+        )
+    }
+}
@@ -0,0 +1,113 @@
+use crate::phonetic::{double_metaphone, metaphone, soundex};
+use crate::FeatureExtractor;
+use lasso::{Rodeo, Spur};
+
+/// Phonetic code used by a [`PhoneticExtractor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhoneticAlgorithm {
+    Soundex,
+    Metaphone,
+    /// Both the primary and the alternate code, when they differ.
+    DoubleMetaphone,
+}
+
+impl PhoneticAlgorithm {
+    /// Returns the non-empty codes of `token`.
+    fn codes(&self, token: &str) -> Vec<String> {
+        let codes = match self {
+            PhoneticAlgorithm::Soundex => vec![soundex(token)],
+            PhoneticAlgorithm::Metaphone => vec![metaphone(token)],
+            PhoneticAlgorithm::DoubleMetaphone => {
+                let (primary, alternate) = double_metaphone(token);
+                if primary == alternate {
+                    vec![primary]
+                } else {
+                    vec![primary, alternate]
+                }
+            }
+        };
+        codes.into_iter().filter(|code| !code.is_empty()).collect()
+    }
+}
+
+/// Turns every word of the text into its phonetic codes, so strings that sound alike, such
+/// as "Catherine" and "Kathryn", share features.
+///
+/// Words are runs of alphabetic characters. Features are the codes themselves or, with
+/// [`PhoneticExtractor::with_ngrams`], the n-grams of each code, which also lets codes that
+/// differ slightly match partially. Like any extractor it can be combined with n-grams of the
+/// raw text in a [`CompositeExtractor`](crate::CompositeExtractor).
+#[derive(Clone)]
+pub struct PhoneticExtractor {
+    algorithm: PhoneticAlgorithm,
+    n: Option<usize>,
+    endmarker: String,
+}
+
+impl PhoneticExtractor {
+    pub fn new(algorithm: PhoneticAlgorithm) -> Self {
+        Self {
+            algorithm,
+            n: None,
+            endmarker: String::new(),
+        }
+    }
+
+    /// Uses the n-grams of each code, padded with `endmarker` like
+    /// [`CharacterNgrams`](crate::CharacterNgrams), instead of whole codes.
+    pub fn with_ngrams(algorithm: PhoneticAlgorithm, n: usize, endmarker: &str) -> Self {
+        Self {
+            algorithm,
+            n: Some(n),
+            endmarker: endmarker.to_string(),
+        }
+    }
+}
+
+impl Default for PhoneticExtractor {
+    fn default() -> Self {
+        Self::new(PhoneticAlgorithm::DoubleMetaphone)
+    }
+}
+
+impl FeatureExtractor for PhoneticExtractor {
+    fn features(&self, text: &str, interner: &mut Rodeo) -> Vec<Spur> {
+        let codes = text
+            .split(|ch: char| !ch.is_alphabetic())
+            .filter(|token| !token.is_empty())
+            .flat_map(|token| self.algorithm.codes(token));
+
+        let features: Vec<String> = match self.n {
+            None => codes.collect(),
+            Some(0) => vec![],
+            Some(n) => {
+                let padding = self.endmarker.repeat(n - 1);
+                codes
+                    .flat_map(|code| {
+                        let chars: Vec<char> = padding
+                            .chars()
+                            .chain(code.chars())
+                            .chain(padding.chars())
+                            .collect();
+                        chars
+                            .windows(n)
+                            .map(|window| window.iter().collect::<String>())
+                            .collect::<Vec<_>>()
+                    })
+                    .collect()
+            }
+        };
+
+        super::append_feature_counts(interner, features)
+    }
+
+    fn descriptor(&self) -> String {
+        match self.n {
+            None => format!("PhoneticExtractor(algorithm={:?})", self.algorithm),
+            Some(n) => format!(
+                "PhoneticExtractor(algorithm={:?}, n={}, endmarker={:?})",
+                self.algorithm, n, self.endmarker
+            ),
+        }
+    }
+}
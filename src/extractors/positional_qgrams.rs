@@ -0,0 +1,70 @@
+use crate::FeatureExtractor;
+use lasso::{Rodeo, Spur};
+
+/// Character n-grams tagged with a coarse position: which of `buckets` equal parts of the
+/// string they start in.
+///
+/// The same n-gram at the start and at the end of a string becomes two different features,
+/// so strings only match when their shared n-grams sit in roughly the same places. Features
+/// are written as `{ngram}@{bucket}:`.
+#[derive(Clone)]
+pub struct PositionalQgrams {
+    n: usize,
+    buckets: usize,
+    endmarker: String,
+}
+
+impl PositionalQgrams {
+    /// `buckets` is raised to 1 if zero.
+    pub fn new(n: usize, buckets: usize, endmarker: &str) -> Self {
+        Self {
+            n,
+            buckets: buckets.max(1),
+            endmarker: endmarker.to_string(),
+        }
+    }
+}
+
+impl Default for PositionalQgrams {
+    fn default() -> Self {
+        Self::new(2, 3, "$")
+    }
+}
+
+impl FeatureExtractor for PositionalQgrams {
+    fn features(&self, text: &str, interner: &mut Rodeo) -> Vec<Spur> {
+        if self.n == 0 {
+            return vec![];
+        }
+
+        let padding = self.endmarker.repeat(self.n.saturating_sub(1));
+        let chars: Vec<char> = padding
+            .chars()
+            .chain(text.chars())
+            .chain(padding.chars())
+            .collect();
+
+        let windows = chars.windows(self.n);
+        let num_ngrams = windows.len();
+        let qgrams: Vec<String> = windows
+            .enumerate()
+            .map(|(position, window)| {
+                let bucket = position * self.buckets / num_ngrams;
+                let mut qgram: String = window.iter().collect();
+                qgram.push('@');
+                qgram.push_str(&bucket.to_string());
+                qgram.push(':');
+                qgram
+            })
+            .collect();
+
+        super::append_feature_counts(interner, qgrams)
+    }
+
+    fn descriptor(&self) -> String {
+        format!(
+            "PositionalQgrams(n={}, buckets={}, endmarker={:?})",
+            self.n, self.buckets, self.endmarker
+        )
+    }
+}
@@ -0,0 +1,108 @@
+use crate::FeatureExtractor;
+use lasso::{Rodeo, Spur};
+use std::collections::BTreeMap;
+
+/// Marker the C++ simstring tool uses for begin/end padding.
+const MARK: char = '\u{1}';
+
+/// Character n-grams generated exactly like the C++ `simstring` tool.
+///
+/// Databases written by the C++ tool index n-grams that differ from [`CharacterNgrams`]:
+/// padding uses a `0x01` marker, only repeated n-grams get an occurrence suffix, and in
+/// byte mode n-grams are taken over UTF-8 bytes rather than chars. Byte-mode features are
+/// stored with each byte mapped to the char of the same value so they stay valid strings.
+///
+/// [`CharacterNgrams`]: super::CharacterNgrams
+#[derive(Clone)]
+pub struct SimstringNgrams {
+    n: usize,
+    begin_end_marks: bool,
+    unicode: bool,
+}
+
+impl SimstringNgrams {
+    /// `begin_end_marks` corresponds to the C++ `-m` flag and `unicode` to `-u`.
+    pub fn new(n: usize, begin_end_marks: bool, unicode: bool) -> Self {
+        Self {
+            n,
+            begin_end_marks,
+            unicode,
+        }
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn begin_end_marks(&self) -> bool {
+        self.begin_end_marks
+    }
+
+    pub fn unicode(&self) -> bool {
+        self.unicode
+    }
+
+    /// Returns the un-interned n-grams of `text`, one entry per feature.
+    pub(crate) fn ngrams(&self, text: &str) -> Vec<String> {
+        if self.n == 0 {
+            return vec![];
+        }
+
+        let units: Vec<char> = if self.unicode {
+            text.chars().collect()
+        } else {
+            text.bytes().map(char::from).collect()
+        };
+
+        let mut src = Vec::with_capacity(units.len() + 2 * self.n);
+        if self.begin_end_marks {
+            src.extend(std::iter::repeat_n(MARK, self.n - 1));
+            src.extend_from_slice(&units);
+            src.extend(std::iter::repeat_n(MARK, self.n - 1));
+        } else {
+            src.extend_from_slice(&units);
+            if src.len() < self.n {
+                src.resize(self.n, MARK);
+            }
+        }
+
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for window in src.windows(self.n) {
+            *counts.entry(window.iter().collect()).or_insert(0) += 1;
+        }
+
+        let mut ngrams = Vec::with_capacity(src.len());
+        for (ngram, count) in counts {
+            for i in 1..count {
+                ngrams.push(format!("{ngram}{}", i + 1));
+            }
+            ngrams.push(ngram);
+        }
+        ngrams
+    }
+}
+
+impl Default for SimstringNgrams {
+    fn default() -> Self {
+        Self::new(3, false, false)
+    }
+}
+
+impl FeatureExtractor for SimstringNgrams {
+    fn features(&self, text: &str, interner: &mut Rodeo) -> Vec<Spur> {
+        let mut features: Vec<Spur> = self
+            .ngrams(text)
+            .into_iter()
+            .map(|ngram| interner.get_or_intern(ngram))
+            .collect();
+        features.sort_unstable();
+        features
+    }
+
+    fn descriptor(&self) -> String {
+        format!(
+            "SimstringNgrams(n={}, begin_end_marks={}, unicode={})",
+            self.n, self.begin_end_marks, self.unicode
+        )
+    }
+}
@@ -0,0 +1,84 @@
+use crate::FeatureExtractor;
+use lasso::{Rodeo, Spur};
+
+/// Character k-skip-n-grams: every `n` characters of the padded text, in order, with at most
+/// `k` characters skipped in total between them.
+///
+/// With `k = 0` this matches [`CharacterNgrams`](crate::CharacterNgrams). Larger `k` adds
+/// features that survive transpositions and single insertions, which suits short noisy
+/// identifiers such as part numbers. A skip-gram does not record which characters it skipped.
+#[derive(Clone)]
+pub struct SkipGrams {
+    n: usize,
+    k: usize,
+    endmarker: String,
+}
+
+impl SkipGrams {
+    pub fn new(n: usize, k: usize, endmarker: &str) -> Self {
+        Self {
+            n,
+            k,
+            endmarker: endmarker.to_string(),
+        }
+    }
+}
+
+impl Default for SkipGrams {
+    fn default() -> Self {
+        Self::new(2, 1, "$")
+    }
+}
+
+/// Pushes every skip-gram extending `gram`, whose last character is at `last`.
+fn extend_skip_grams(
+    chars: &[char],
+    gram: &mut String,
+    last: usize,
+    remaining: usize,
+    skips_left: usize,
+    out: &mut Vec<String>,
+) {
+    if remaining == 0 {
+        out.push(gram.clone());
+        return;
+    }
+    for next in last + 1..=(last + 1 + skips_left).min(chars.len().saturating_sub(1)) {
+        gram.push(chars[next]);
+        let skipped = next - last - 1;
+        extend_skip_grams(chars, gram, next, remaining - 1, skips_left - skipped, out);
+        gram.pop();
+    }
+}
+
+impl FeatureExtractor for SkipGrams {
+    fn features(&self, text: &str, interner: &mut Rodeo) -> Vec<Spur> {
+        if self.n == 0 {
+            return vec![];
+        }
+
+        let padding = self.endmarker.repeat(self.n.saturating_sub(1));
+        let chars: Vec<char> = padding
+            .chars()
+            .chain(text.chars())
+            .chain(padding.chars())
+            .collect();
+
+        let mut grams = Vec::new();
+        let mut gram = String::with_capacity(self.n * 4);
+        for (start, &ch) in chars.iter().enumerate() {
+            gram.push(ch);
+            extend_skip_grams(&chars, &mut gram, start, self.n - 1, self.k, &mut grams);
+            gram.clear();
+        }
+
+        super::append_feature_counts(interner, grams)
+    }
+
+    fn descriptor(&self) -> String {
+        format!(
+            "SkipGrams(n={}, k={}, endmarker={:?})",
+            self.n, self.k, self.endmarker
+        )
+    }
+}
@@ -1,3 +1,4 @@
+use super::parse_debug_str;
 use crate::FeatureExtractor;
 use lasso::{Rodeo, Spur};
 
@@ -16,6 +17,23 @@ impl WordNgrams {
             padder: padder.to_string(),
         }
     }
+
+    /// Rebuilds the extractor a [`descriptor`](FeatureExtractor::descriptor) was produced by,
+    /// e.g. the one recorded in a saved database. Returns `None` for any other descriptor.
+    pub fn from_descriptor(descriptor: &str) -> Option<Self> {
+        let rest = descriptor.strip_prefix("WordNgrams(n=")?;
+        let (n, rest) = rest.split_once(", splitter=")?;
+        let (splitter, rest) = parse_debug_str(rest)?;
+        let (padder, rest) = parse_debug_str(rest.strip_prefix(", padder=")?)?;
+        if rest != ")" {
+            return None;
+        }
+        Some(Self {
+            n: n.parse().ok()?,
+            splitter,
+            padder,
+        })
+    }
 }
 
 impl Default for WordNgrams {
@@ -51,4 +69,11 @@ impl FeatureExtractor for WordNgrams {
 
         super::append_feature_counts(interner, ngrams)
     }
+
+    fn descriptor(&self) -> String {
+        format!(
+            "WordNgrams(n={}, splitter={:?}, padder={:?})",
+            self.n, self.splitter, self.padder
+        )
+    }
 }
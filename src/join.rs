@@ -0,0 +1,136 @@
+use crate::database::{Database, StringId};
+use crate::measures::Measure;
+use crate::search::{check_threshold, SearchError, Searcher};
+use rayon::prelude::*;
+use std::sync::Mutex;
+
+/// Finds every pair of strings in `db` with similarity of at least `alpha`, calling `on_pair`
+/// with `(a, b, score)` as pairs are found.
+///
+/// Each string is probed against the index in parallel, so `on_pair` may be called from
+/// several threads at once and in no particular order. Pairs are reported once, with
+/// `a < b`; a string is never paired with itself.
+pub fn self_join_with<M, F>(
+    db: &dyn Database,
+    measure: M,
+    alpha: f64,
+    on_pair: F,
+) -> Result<(), SearchError>
+where
+    M: Measure,
+    F: Fn(StringId, StringId, f64) + Sync,
+{
+    check_threshold(alpha)?;
+    let searcher = Searcher::new(db, measure);
+
+    (0..db.string_id_bound()).into_par_iter().for_each(|a| {
+        let Some(features) = db.get_features(a) else {
+            return;
+        };
+        // Only probe forward so each pair is generated and scored once
+        for b in searcher.search_for_ids_after(features, alpha, Some(a)) {
+            if let Some(candidate_features) = db.get_features(b) {
+                let score = searcher.measure().similarity(features, candidate_features);
+                if score >= alpha {
+                    on_pair(a, b, score);
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Collects the pairs found by [`self_join_with`], sorted by `(a, b)`.
+pub fn self_join<M: Measure>(
+    db: &dyn Database,
+    measure: M,
+    alpha: f64,
+) -> Result<Vec<(StringId, StringId, f64)>, SearchError> {
+    let pairs = Mutex::new(Vec::new());
+    self_join_with(db, measure, alpha, |a, b, score| {
+        pairs.lock().unwrap().push((a, b, score));
+    })?;
+
+    let mut pairs = pairs.into_inner().unwrap();
+    pairs.sort_unstable_by_key(|&(a, b, _)| (a, b));
+    Ok(pairs)
+}
+
+/// Finds every pair `(a, b)` of a string `a` in `db_a` and a string `b` in `db_b` with
+/// similarity of at least `alpha`, calling `on_pair` with `(a, b, score)`.
+///
+/// The strings of the smaller database are probed in parallel against the index of the other
+/// one, so `on_pair` may be called from several threads at once. Both databases must use
+/// identically configured feature extractors.
+pub fn similarity_join_with<M, F>(
+    db_a: &dyn Database,
+    db_b: &dyn Database,
+    measure: M,
+    alpha: f64,
+    on_pair: F,
+) -> Result<(), SearchError>
+where
+    M: Measure,
+    F: Fn(StringId, StringId, f64) + Sync,
+{
+    check_threshold(alpha)?;
+    let descriptor_a = db_a.feature_extractor().descriptor();
+    let descriptor_b = db_b.feature_extractor().descriptor();
+    if descriptor_a != descriptor_b {
+        return Err(SearchError::IncompatibleExtractors(
+            descriptor_a,
+            descriptor_b,
+        ));
+    }
+
+    // Probe the larger index with the strings of the smaller database
+    let swapped = db_a.total_strings() > db_b.total_strings();
+    let (probe, indexed) = if swapped { (db_b, db_a) } else { (db_a, db_b) };
+    let searcher = Searcher::new(indexed, measure);
+
+    let (probe_ids, probe_strings): (Vec<StringId>, Vec<&str>) = (0..probe.string_id_bound())
+        .filter_map(|id| probe.get_string(id).map(|text| (id, text)))
+        .unzip();
+    // Features must be re-extracted in the namespace of the indexed database
+    let probe_features = searcher.batch_query_features(&probe_strings);
+
+    probe_ids
+        .par_iter()
+        .zip(probe_features.par_iter())
+        .for_each(|(&probe_id, features)| {
+            for indexed_id in searcher.search_for_ids(features, alpha) {
+                if let Some(candidate_features) = indexed.get_features(indexed_id) {
+                    let score = searcher.measure().similarity(features, candidate_features);
+                    if score < alpha {
+                        continue;
+                    }
+                    // Report pairs in `(db_a, db_b)` order regardless of which side was probed
+                    if swapped {
+                        on_pair(indexed_id, probe_id, score);
+                    } else {
+                        on_pair(probe_id, indexed_id, score);
+                    }
+                }
+            }
+        });
+
+    Ok(())
+}
+
+/// Collects the pairs found by [`similarity_join_with`], sorted by `(a, b)`.
+pub fn similarity_join<M: Measure>(
+    db_a: &dyn Database,
+    db_b: &dyn Database,
+    measure: M,
+    alpha: f64,
+) -> Result<Vec<(StringId, StringId, f64)>, SearchError> {
+    let pairs = Mutex::new(Vec::new());
+    similarity_join_with(db_a, db_b, measure, alpha, |a, b, score| {
+        pairs.lock().unwrap().push((a, b, score));
+    })?;
+
+    let mut pairs = pairs.into_inner().unwrap();
+    pairs.sort_unstable_by_key(|&(a, b, _)| (a, b));
+    Ok(pairs)
+}
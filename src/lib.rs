@@ -1,10 +1,22 @@
+pub mod cluster;
 pub mod database;
+pub mod distance;
 pub mod extractors;
+pub mod join;
 pub mod measures;
+pub mod phonetic;
 pub mod python;
 pub mod search;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod suggest;
+pub mod tagger;
 
-pub use database::{Database, HashDb};
-pub use extractors::{CharacterNgrams, FeatureExtractor, WordNgrams};
+pub use database::{ConcurrentHashDb, Database, DatabaseMut, FrozenHashDb, HashDb, PersistError};
+pub use extractors::{
+    CharacterNgrams, CompositeExtractor, FeatureExtractor, GraphemeNgrams, Normalization,
+    Normalizer, PhoneticAlgorithm, PhoneticExtractor, PositionalQgrams, SimstringNgrams, SkipGrams,
+    WordNgrams,
+};
 pub use measures::{Cosine, Dice, ExactMatch, Jaccard, Measure, Overlap};
-pub use search::{SearchError, Searcher};
+pub use search::{SearchError, Searcher, Verification};
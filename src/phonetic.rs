@@ -0,0 +1,860 @@
+//! Phonetic codes for English words and names, so that words sounding alike get equal codes.
+//!
+//! Only ASCII letters are coded (plus `Ç` and `Ñ` in Double Metaphone); other characters are
+//! ignored.
+
+/// American Soundex: the first letter followed by three digits, e.g. `"R163"` for "Robert".
+///
+/// Returns an empty string if `word` has no ASCII letters.
+pub fn soundex(word: &str) -> String {
+    fn digit(ch: char) -> char {
+        match ch {
+            'B' | 'F' | 'P' | 'V' => '1',
+            'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => '2',
+            'D' | 'T' => '3',
+            'L' => '4',
+            'M' | 'N' => '5',
+            'R' => '6',
+            _ => '0',
+        }
+    }
+
+    let mut letters = word
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|ch| ch.to_ascii_uppercase());
+    let Some(first) = letters.next() else {
+        return String::new();
+    };
+
+    let mut code = String::with_capacity(4);
+    code.push(first);
+    let mut last_digit = digit(first);
+    for ch in letters {
+        if code.len() == 4 {
+            break;
+        }
+        // H and W do not separate letters with the same digit, vowels do
+        if ch == 'H' || ch == 'W' {
+            continue;
+        }
+        let d = digit(ch);
+        if d != '0' && d != last_digit {
+            code.push(d);
+        }
+        last_digit = d;
+    }
+    while code.len() < 4 {
+        code.push('0');
+    }
+    code
+}
+
+fn is_vowel(ch: char) -> bool {
+    matches!(ch, 'A' | 'E' | 'I' | 'O' | 'U')
+}
+
+/// Lawrence Philips' original Metaphone, e.g. `"K0RN"` for both "Catherine" and "Kathryn".
+///
+/// `0` stands for "th" and `X` for "sh". The code is not truncated.
+pub fn metaphone(word: &str) -> String {
+    let mut letters: Vec<char> = Vec::with_capacity(word.len());
+    for ch in word.chars().filter(char::is_ascii_alphabetic) {
+        let ch = ch.to_ascii_uppercase();
+        // Duplicate letters are dropped, except for C
+        if letters.last() != Some(&ch) || ch == 'C' {
+            letters.push(ch);
+        }
+    }
+
+    let mut start = 0;
+    let mut code = String::with_capacity(letters.len());
+    match letters.as_slice() {
+        ['K', 'N', ..] | ['G', 'N', ..] | ['P', 'N', ..] | ['A', 'E', ..] | ['W', 'R', ..] => {
+            start = 1
+        }
+        ['X', ..] => {
+            code.push('S');
+            start = 1;
+        }
+        ['W', 'H', ..] => {
+            code.push('W');
+            start = 2;
+        }
+        _ => {}
+    }
+
+    let at = |i: usize| letters.get(i).copied().unwrap_or('\0');
+    let last = letters.len().saturating_sub(1);
+    for i in start..letters.len() {
+        let ch = letters[i];
+        let prev = if i > 0 { at(i - 1) } else { '\0' };
+        let (next, after_next) = (at(i + 1), at(i + 2));
+        match ch {
+            'A' | 'E' | 'I' | 'O' | 'U' => {
+                if i == 0 {
+                    code.push(ch);
+                }
+            }
+            'B' => {
+                if !(i == last && prev == 'M') {
+                    code.push('B');
+                }
+            }
+            'C' => {
+                if next == 'I' && after_next == 'A' {
+                    code.push('X');
+                } else if next == 'H' {
+                    code.push(if prev == 'S' { 'K' } else { 'X' });
+                } else if matches!(next, 'I' | 'E' | 'Y') {
+                    if prev != 'S' {
+                        code.push('S');
+                    }
+                } else {
+                    code.push('K');
+                }
+            }
+            'D' => {
+                if next == 'G' && matches!(after_next, 'E' | 'Y' | 'I') {
+                    code.push('J');
+                } else {
+                    code.push('T');
+                }
+            }
+            'G' => {
+                let silent = (next == 'H' && i + 1 != last && !is_vowel(after_next))
+                    || (next == 'N' && (i + 1 == last || letters[i + 1..] == ['N', 'E', 'D']))
+                    || (prev == 'D' && matches!(next, 'E' | 'I' | 'Y'));
+                if !silent {
+                    code.push(if matches!(next, 'I' | 'E' | 'Y') {
+                        'J'
+                    } else {
+                        'K'
+                    });
+                }
+            }
+            'H' => {
+                let after_digraph = matches!(prev, 'C' | 'S' | 'P' | 'T' | 'G');
+                let between = is_vowel(prev) && !is_vowel(next);
+                if !after_digraph && !between {
+                    code.push('H');
+                }
+            }
+            'K' => {
+                if prev != 'C' {
+                    code.push('K');
+                }
+            }
+            'P' => code.push(if next == 'H' { 'F' } else { 'P' }),
+            'Q' => code.push('K'),
+            'S' => {
+                if next == 'H' || (next == 'I' && matches!(after_next, 'O' | 'A')) {
+                    code.push('X');
+                } else {
+                    code.push('S');
+                }
+            }
+            'T' => {
+                if next == 'I' && matches!(after_next, 'O' | 'A') {
+                    code.push('X');
+                } else if next == 'H' {
+                    code.push('0');
+                } else if !(next == 'C' && after_next == 'H') {
+                    code.push('T');
+                }
+            }
+            'V' => code.push('F'),
+            'W' | 'Y' => {
+                if is_vowel(next) {
+                    code.push(ch);
+                }
+            }
+            'X' => code.push_str("KS"),
+            'Z' => code.push('S'),
+            _ => code.push(ch),
+        }
+    }
+    code
+}
+
+/// Longest code [`double_metaphone`] returns.
+const DOUBLE_METAPHONE_LEN: usize = 4;
+
+/// State of a Double Metaphone encoding: the upper-cased word padded with spaces, so that
+/// lookaheads past its end see blanks, and the two codes built so far.
+struct DoubleMetaphone {
+    chars: Vec<char>,
+    length: usize,
+    primary: String,
+    secondary: String,
+}
+
+impl DoubleMetaphone {
+    fn new(word: &str) -> Self {
+        let mut chars: Vec<char> = word.chars().flat_map(char::to_uppercase).collect();
+        let length = chars.len();
+        chars.extend([' '; 5]);
+        Self {
+            chars,
+            length,
+            primary: String::new(),
+            secondary: String::new(),
+        }
+    }
+
+    fn at(&self, pos: isize) -> char {
+        if pos < 0 {
+            return '\0';
+        }
+        self.chars.get(pos as usize).copied().unwrap_or('\0')
+    }
+
+    /// Whether the `len` characters at `start` equal one of `options`.
+    fn string_at(&self, start: isize, len: usize, options: &[&str]) -> bool {
+        if start < 0 || start as usize + len > self.chars.len() {
+            return false;
+        }
+        let start = start as usize;
+        let window = &self.chars[start..start + len];
+        options
+            .iter()
+            .any(|option| option.chars().eq(window.iter().copied()))
+    }
+
+    fn is_vowel(&self, pos: isize) -> bool {
+        matches!(self.at(pos), 'A' | 'E' | 'I' | 'O' | 'U' | 'Y')
+    }
+
+    fn is_slavo_germanic(&self) -> bool {
+        let word: String = self.chars[..self.length].iter().collect();
+        word.contains('W') || word.contains('K') || word.contains("CZ") || word.contains("WITZ")
+    }
+
+    fn add(&mut self, code: &str) {
+        self.add2(code, code);
+    }
+
+    fn add2(&mut self, primary: &str, secondary: &str) {
+        self.primary.push_str(primary);
+        self.secondary.push_str(secondary);
+    }
+
+    fn encode(mut self) -> (String, String) {
+        let length = self.length as isize;
+        let last = length - 1;
+        let slavo_germanic = self.is_slavo_germanic();
+        let mut current: isize = 0;
+
+        // Silent first letters
+        if self.string_at(0, 2, &["GN", "KN", "PN", "WR", "PS"]) {
+            current += 1;
+        }
+        // Initial X is pronounced Z, which maps to S
+        if self.at(0) == 'X' {
+            self.add("S");
+            current += 1;
+        }
+
+        while (self.primary.len() < DOUBLE_METAPHONE_LEN
+            || self.secondary.len() < DOUBLE_METAPHONE_LEN)
+            && current < length
+        {
+            current += match self.at(current) {
+                'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => {
+                    if current == 0 {
+                        self.add("A");
+                    }
+                    1
+                }
+                'B' => {
+                    self.add("P");
+                    if self.at(current + 1) == 'B' {
+                        2
+                    } else {
+                        1
+                    }
+                }
+                'Ç' => {
+                    self.add("S");
+                    1
+                }
+                'C' => self.encode_c(current),
+                'D' => {
+                    if self.string_at(current, 2, &["DG"]) {
+                        if self.string_at(current + 2, 1, &["I", "E", "Y"]) {
+                            // "edge"
+                            self.add("J");
+                            3
+                        } else {
+                            // "edgar"
+                            self.add("TK");
+                            2
+                        }
+                    } else if self.string_at(current, 2, &["DT", "DD"]) {
+                        self.add("T");
+                        2
+                    } else {
+                        self.add("T");
+                        1
+                    }
+                }
+                'F' => {
+                    self.add("F");
+                    if self.at(current + 1) == 'F' {
+                        2
+                    } else {
+                        1
+                    }
+                }
+                'G' => self.encode_g(current, slavo_germanic),
+                // Only kept between vowels, or first and before a vowel
+                'H' if (current == 0 || self.is_vowel(current - 1))
+                    && self.is_vowel(current + 1) =>
+                {
+                    self.add("H");
+                    2
+                }
+                'J' => self.encode_j(current, last, slavo_germanic),
+                'K' => {
+                    self.add("K");
+                    if self.at(current + 1) == 'K' {
+                        2
+                    } else {
+                        1
+                    }
+                }
+                'L' => {
+                    if self.at(current + 1) == 'L' {
+                        // Spanish "cabrillo", "gallegos"
+                        let spanish = (current == length - 3
+                            && self.string_at(current - 1, 4, &["ILLO", "ILLA", "ALLE"]))
+                            || ((self.string_at(last - 1, 2, &["AS", "OS"])
+                                || self.string_at(last, 1, &["A", "O"]))
+                                && self.string_at(current - 1, 4, &["ALLE"]));
+                        if spanish {
+                            self.add2("L", "");
+                        } else {
+                            self.add("L");
+                        }
+                        2
+                    } else {
+                        self.add("L");
+                        1
+                    }
+                }
+                'M' => {
+                    self.add("M");
+                    // "dumb", "thumb"
+                    let silent_b = self.string_at(current - 1, 3, &["UMB"])
+                        && (current + 1 == last || self.string_at(current + 2, 2, &["ER"]));
+                    if silent_b || self.at(current + 1) == 'M' {
+                        2
+                    } else {
+                        1
+                    }
+                }
+                'N' => {
+                    self.add("N");
+                    if self.at(current + 1) == 'N' {
+                        2
+                    } else {
+                        1
+                    }
+                }
+                'Ñ' => {
+                    self.add("N");
+                    1
+                }
+                'P' => {
+                    if self.at(current + 1) == 'H' {
+                        self.add("F");
+                        2
+                    } else {
+                        self.add("P");
+                        // "campbell", "raspberry"
+                        if self.string_at(current + 1, 1, &["P", "B"]) {
+                            2
+                        } else {
+                            1
+                        }
+                    }
+                }
+                'Q' => {
+                    self.add("K");
+                    if self.at(current + 1) == 'Q' {
+                        2
+                    } else {
+                        1
+                    }
+                }
+                'R' => {
+                    // French "rogier", but not "hochmeier"
+                    if current == last
+                        && !slavo_germanic
+                        && self.string_at(current - 2, 2, &["IE"])
+                        && !self.string_at(current - 4, 2, &["ME", "MA"])
+                    {
+                        self.add2("", "R");
+                    } else {
+                        self.add("R");
+                    }
+                    if self.at(current + 1) == 'R' {
+                        2
+                    } else {
+                        1
+                    }
+                }
+                'S' => self.encode_s(current, last, slavo_germanic),
+                'T' => {
+                    if self.string_at(current, 4, &["TION"])
+                        || self.string_at(current, 3, &["TIA", "TCH"])
+                    {
+                        self.add("X");
+                        3
+                    } else if self.string_at(current, 2, &["TH"])
+                        || self.string_at(current, 3, &["TTH"])
+                    {
+                        // "thomas", "thames"
+                        if self.string_at(current + 2, 2, &["OM", "AM"])
+                            || self.string_at(0, 4, &["VAN ", "VON "])
+                            || self.string_at(0, 3, &["SCH"])
+                        {
+                            self.add("T");
+                        } else {
+                            self.add2("0", "T");
+                        }
+                        2
+                    } else {
+                        self.add("T");
+                        if self.string_at(current + 1, 1, &["T", "D"]) {
+                            2
+                        } else {
+                            1
+                        }
+                    }
+                }
+                'V' => {
+                    self.add("F");
+                    if self.at(current + 1) == 'V' {
+                        2
+                    } else {
+                        1
+                    }
+                }
+                'W' => self.encode_w(current, last),
+                'X' => {
+                    // French "breaux"
+                    let silent = current == last
+                        && (self.string_at(current - 3, 3, &["IAU", "EAU"])
+                            || self.string_at(current - 2, 2, &["AU", "OU"]));
+                    if !silent {
+                        self.add("KS");
+                    }
+                    if self.string_at(current + 1, 1, &["C", "X"]) {
+                        2
+                    } else {
+                        1
+                    }
+                }
+                'Z' => {
+                    if self.at(current + 1) == 'H' {
+                        // Chinese "zhao"
+                        self.add("J");
+                        2
+                    } else {
+                        if self.string_at(current + 1, 2, &["ZO", "ZI", "ZA"])
+                            || (slavo_germanic && current > 0 && self.at(current - 1) != 'T')
+                        {
+                            self.add2("S", "TS");
+                        } else {
+                            self.add("S");
+                        }
+                        if self.at(current + 1) == 'Z' {
+                            2
+                        } else {
+                            1
+                        }
+                    }
+                }
+                _ => 1,
+            };
+        }
+
+        self.primary.truncate(DOUBLE_METAPHONE_LEN);
+        self.secondary.truncate(DOUBLE_METAPHONE_LEN);
+        (self.primary, self.secondary)
+    }
+
+    fn encode_c(&mut self, current: isize) -> isize {
+        // Germanic "bacher", "macher"
+        if current > 1
+            && !self.is_vowel(current - 2)
+            && self.string_at(current - 1, 3, &["ACH"])
+            && self.at(current + 2) != 'I'
+            && (self.at(current + 2) != 'E'
+                || self.string_at(current - 2, 6, &["BACHER", "MACHER"]))
+        {
+            self.add("K");
+            return 2;
+        }
+        if current == 0 && self.string_at(current, 6, &["CAESAR"]) {
+            self.add("S");
+            return 2;
+        }
+        // Italian "chianti"
+        if self.string_at(current, 4, &["CHIA"]) {
+            self.add("K");
+            return 2;
+        }
+        if self.string_at(current, 2, &["CH"]) {
+            // "michael"
+            if current > 0 && self.string_at(current, 4, &["CHAE"]) {
+                self.add2("K", "X");
+                return 2;
+            }
+            // Greek roots, "chemistry", "chorus"
+            if current == 0
+                && (self.string_at(current + 1, 5, &["HARAC", "HARIS"])
+                    || self.string_at(current + 1, 3, &["HOR", "HYM", "HIA", "HEM"]))
+                && !self.string_at(0, 5, &["CHORE"])
+            {
+                self.add("K");
+                return 2;
+            }
+            // Germanic, Greek, or otherwise "ch" for "kh" sound
+            if self.string_at(0, 4, &["VAN ", "VON "])
+                || self.string_at(0, 3, &["SCH"])
+                || self.string_at(current - 2, 6, &["ORCHES", "ARCHIT", "ORCHID"])
+                || self.string_at(current + 2, 1, &["T", "S"])
+                || ((self.string_at(current - 1, 1, &["A", "O", "U", "E"]) || current == 0)
+                    && self.string_at(
+                        current + 2,
+                        1,
+                        &["L", "R", "N", "M", "B", "H", "F", "V", "W", " "],
+                    ))
+            {
+                self.add("K");
+            } else if current > 0 {
+                if self.string_at(0, 2, &["MC"]) {
+                    self.add("K");
+                } else {
+                    self.add2("X", "K");
+                }
+            } else {
+                self.add("X");
+            }
+            return 2;
+        }
+        // Polish "czerny"
+        if self.string_at(current, 2, &["CZ"]) && !self.string_at(current - 2, 4, &["WICZ"]) {
+            self.add2("S", "X");
+            return 2;
+        }
+        // Italian "focaccia"
+        if self.string_at(current + 1, 3, &["CIA"]) {
+            self.add("X");
+            return 3;
+        }
+        // Double C, but not "McClellan"
+        if self.string_at(current, 2, &["CC"]) && !(current == 1 && self.at(0) == 'M') {
+            // "bellocchio", but not "bacchus"
+            if self.string_at(current + 2, 1, &["I", "E", "H"])
+                && !self.string_at(current + 2, 2, &["HU"])
+            {
+                // "accident", "accede", "succeed"
+                if (current == 1 && self.at(current - 1) == 'A')
+                    || self.string_at(current - 1, 5, &["UCCEE", "UCCES"])
+                {
+                    self.add("KS");
+                } else {
+                    self.add("X");
+                }
+                return 3;
+            }
+            // Pierce's rule
+            self.add("K");
+            return 2;
+        }
+        if self.string_at(current, 2, &["CK", "CG", "CQ"]) {
+            self.add("K");
+            return 2;
+        }
+        if self.string_at(current, 2, &["CI", "CE", "CY"]) {
+            // Italian vs. English
+            if self.string_at(current, 3, &["CIO", "CIE", "CIA"]) {
+                self.add2("S", "X");
+            } else {
+                self.add("S");
+            }
+            return 2;
+        }
+
+        self.add("K");
+        // "mac caffrey", "mac gregor"
+        if self.string_at(current + 1, 2, &[" C", " Q", " G"]) {
+            3
+        } else if self.string_at(current + 1, 1, &["C", "K", "Q"])
+            && !self.string_at(current + 1, 2, &["CE", "CI"])
+        {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn encode_g(&mut self, current: isize, slavo_germanic: bool) -> isize {
+        if self.at(current + 1) == 'H' {
+            if current > 0 && !self.is_vowel(current - 1) {
+                self.add("K");
+                return 2;
+            }
+            // "ghislane", "ghiradelli"
+            if current == 0 {
+                if self.at(current + 2) == 'I' {
+                    self.add("J");
+                } else {
+                    self.add("K");
+                }
+                return 2;
+            }
+            // Parker's rule, "hugh", "bough", "broughton"
+            if (current > 1 && self.string_at(current - 2, 1, &["B", "H", "D"]))
+                || (current > 2 && self.string_at(current - 3, 1, &["B", "H", "D"]))
+                || (current > 3 && self.string_at(current - 4, 1, &["B", "H"]))
+            {
+                return 2;
+            }
+            // "laugh", "mclaughlin", "cough", "gough", "rough", "tough"
+            if current > 2
+                && self.at(current - 1) == 'U'
+                && self.string_at(current - 3, 1, &["C", "G", "L", "R", "T"])
+            {
+                self.add("F");
+            } else if current > 0 && self.at(current - 1) != 'I' {
+                self.add("K");
+            }
+            return 2;
+        }
+
+        if self.at(current + 1) == 'N' {
+            if current == 1 && self.is_vowel(0) && !slavo_germanic {
+                self.add2("KN", "N");
+            } else if !self.string_at(current + 2, 2, &["EY"])
+                && self.at(current + 1) != 'Y'
+                && !slavo_germanic
+            {
+                // Not "cagney"
+                self.add2("N", "KN");
+            } else {
+                self.add("KN");
+            }
+            return 2;
+        }
+
+        // "tagliaro"
+        if self.string_at(current + 1, 2, &["LI"]) && !slavo_germanic {
+            self.add2("KL", "L");
+            return 2;
+        }
+
+        // -ges-, -gep-, -gel-, -gie- at the beginning
+        if current == 0
+            && (self.at(current + 1) == 'Y'
+                || self.string_at(
+                    current + 1,
+                    2,
+                    &[
+                        "ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER",
+                    ],
+                ))
+        {
+            self.add2("K", "J");
+            return 2;
+        }
+
+        // -ger-, -gy-
+        if (self.string_at(current + 1, 2, &["ER"]) || self.at(current + 1) == 'Y')
+            && !self.string_at(0, 6, &["DANGER", "RANGER", "MANGER"])
+            && !self.string_at(current - 1, 1, &["E", "I"])
+            && !self.string_at(current - 1, 3, &["RGY", "OGY"])
+        {
+            self.add2("K", "J");
+            return 2;
+        }
+
+        // Italian "biaggi"
+        if self.string_at(current + 1, 1, &["E", "I", "Y"])
+            || self.string_at(current - 1, 4, &["AGGI", "OGGI"])
+        {
+            // Obvious Germanic
+            if self.string_at(0, 4, &["VAN ", "VON "])
+                || self.string_at(0, 3, &["SCH"])
+                || self.string_at(current + 1, 2, &["ET"])
+            {
+                self.add("K");
+            } else if self.string_at(current + 1, 4, &["IER "]) {
+                self.add("J");
+            } else {
+                self.add2("J", "K");
+            }
+            return 2;
+        }
+
+        self.add("K");
+        if self.at(current + 1) == 'G' {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn encode_j(&mut self, current: isize, last: isize, slavo_germanic: bool) -> isize {
+        // Spanish "jose", "san jacinto"
+        if self.string_at(current, 4, &["JOSE"]) || self.string_at(0, 4, &["SAN "]) {
+            if (current == 0 && self.at(current + 4) == ' ') || self.string_at(0, 4, &["SAN "]) {
+                self.add("H");
+            } else {
+                self.add2("J", "H");
+            }
+            return 1;
+        }
+
+        if current == 0 && !self.string_at(current, 4, &["JOSE"]) {
+            // "Yankelovich", "Jankelowicz"
+            self.add2("J", "A");
+        } else if self.is_vowel(current - 1)
+            && !slavo_germanic
+            && matches!(self.at(current + 1), 'A' | 'O')
+        {
+            // Spanish "bajador"
+            self.add2("J", "H");
+        } else if current == last {
+            self.add2("J", "");
+        } else if !self.string_at(current + 1, 1, &["L", "T", "K", "S", "N", "M", "B", "Z"])
+            && !self.string_at(current - 1, 1, &["S", "K", "L"])
+        {
+            self.add("J");
+        }
+
+        if self.at(current + 1) == 'J' {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn encode_s(&mut self, current: isize, last: isize, slavo_germanic: bool) -> isize {
+        // "island", "isle", "carlisle", "carlysle"
+        if self.string_at(current - 1, 3, &["ISL", "YSL"]) {
+            return 1;
+        }
+        // "sugar"
+        if current == 0 && self.string_at(current, 5, &["SUGAR"]) {
+            self.add2("X", "S");
+            return 1;
+        }
+        if self.string_at(current, 2, &["SH"]) {
+            // Germanic
+            if self.string_at(current + 1, 4, &["HEIM", "HOEK", "HOLM", "HOLZ"]) {
+                self.add("S");
+            } else {
+                self.add("X");
+            }
+            return 2;
+        }
+        // Italian and Armenian
+        if self.string_at(current, 3, &["SIO", "SIA"]) || self.string_at(current, 4, &["SIAN"]) {
+            if slavo_germanic {
+                self.add("S");
+            } else {
+                self.add2("S", "X");
+            }
+            return 3;
+        }
+        // German and anglicisations, "smith" and "schmidt", "snider" and "schneider"
+        if (current == 0 && self.string_at(current + 1, 1, &["M", "N", "L", "W"]))
+            || self.string_at(current + 1, 1, &["Z"])
+        {
+            self.add2("S", "X");
+            return if self.string_at(current + 1, 1, &["Z"]) {
+                2
+            } else {
+                1
+            };
+        }
+        if self.string_at(current, 2, &["SC"]) {
+            // Schlesinger's rule
+            if self.at(current + 2) == 'H' {
+                // Dutch origin, "school", "schooner"
+                if self.string_at(current + 3, 2, &["OO", "ER", "EN", "UY", "ED", "EM"]) {
+                    // "schermerhorn", "schenker"
+                    if self.string_at(current + 3, 2, &["ER", "EN"]) {
+                        self.add2("X", "SK");
+                    } else {
+                        self.add("SK");
+                    }
+                } else if current == 0 && !self.is_vowel(3) && self.at(3) != 'W' {
+                    self.add2("X", "S");
+                } else {
+                    self.add("X");
+                }
+                return 3;
+            }
+            if self.string_at(current + 2, 1, &["I", "E", "Y"]) {
+                self.add("S");
+            } else {
+                self.add("SK");
+            }
+            return 3;
+        }
+
+        // French "resnais", "artois"
+        if current == last && self.string_at(current - 2, 2, &["AI", "OI"]) {
+            self.add2("", "S");
+        } else {
+            self.add("S");
+        }
+        if self.string_at(current + 1, 1, &["S", "Z"]) {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn encode_w(&mut self, current: isize, last: isize) -> isize {
+        if self.string_at(current, 2, &["WR"]) {
+            self.add("R");
+            return 2;
+        }
+        if current == 0 && (self.is_vowel(current + 1) || self.string_at(current, 2, &["WH"])) {
+            // "Wasserman" should match "Vasserman"
+            if self.is_vowel(current + 1) {
+                self.add2("A", "F");
+            } else {
+                self.add("A");
+            }
+        }
+        // Polish "filipowicz", and "arnow" which may be pronounced either way
+        if (current == last && self.is_vowel(current - 1))
+            || self.string_at(current - 1, 5, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"])
+            || self.string_at(0, 3, &["SCH"])
+        {
+            self.add2("", "F");
+            return 1;
+        }
+        if self.string_at(current, 4, &["WICZ", "WITZ"]) {
+            self.add2("TS", "FX");
+            return 4;
+        }
+        1
+    }
+}
+
+/// Lawrence Philips' Double Metaphone, returning a primary and an alternate code of up to
+/// four characters each.
+///
+/// The codes are equal unless the word has a plausible second pronunciation, e.g.
+/// `("SM0", "XMT")` for "Smith".
+pub fn double_metaphone(word: &str) -> (String, String) {
+    DoubleMetaphone::new(word).encode()
+}
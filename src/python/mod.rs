@@ -1,5 +1,5 @@
 use crate::{
-    database::{Database, HashDb},
+    database::{Database, HashDb, PersistError as RustPersistError},
     extractors::{CharacterNgrams, FeatureExtractor, WordNgrams},
     measures::{Cosine, Dice, ExactMatch, Jaccard, Measure, Overlap},
     search::{SearchError as RustSearchError, Searcher as RustSearcher},
@@ -47,6 +47,19 @@ impl CustomExtractorInner {
         Ok(crate::extractors::append_feature_counts(interner, raw))
     }
 
+    fn descriptor(&self) -> String {
+        let extractor = Arc::clone(&self.extractor);
+        Python::attach(|py| {
+            let type_name = extractor
+                .bind(py)
+                .get_type()
+                .qualname()
+                .map(|name| name.to_string())
+                .unwrap_or_else(|_| "<unknown>".to_string());
+            format!("CustomExtractor({type_name})")
+        })
+    }
+
     fn apply(&self, text: &str) -> PyResult<Vec<String>> {
         let raw = self.collect_raw_features(text)?;
         let mut interner = lasso::Rodeo::default();
@@ -81,6 +94,44 @@ impl FeatureExtractor for PyFeatureExtractor {
             },
         }
     }
+
+    fn descriptor(&self) -> String {
+        match self {
+            PyFeatureExtractor::Character(e) => e.descriptor(),
+            PyFeatureExtractor::Word(e) => e.descriptor(),
+            PyFeatureExtractor::Custom(e) => e.descriptor(),
+        }
+    }
+}
+
+fn to_py_feature_extractor(extractor: &Bound<'_, PyAny>) -> PyResult<PyFeatureExtractor> {
+    if let Ok(char_ngram) = extractor.extract::<PyRef<PyCharacterNgrams>>() {
+        Ok(PyFeatureExtractor::Character(char_ngram.0.clone()))
+    } else if let Ok(word_ngram) = extractor.extract::<PyRef<PyWordNgrams>>() {
+        Ok(PyFeatureExtractor::Word(word_ngram.0.clone()))
+    } else if let Ok(custom) = extractor.extract::<PyRef<PyCustomExtractor>>() {
+        Ok(PyFeatureExtractor::Custom(custom.0.clone()))
+    } else {
+        Err(pyo3::exceptions::PyTypeError::new_err(
+            "Extractor must be CharacterNgrams, WordNgrams, or CustomExtractor",
+        ))
+    }
+}
+
+fn search_error_to_py(err: RustSearchError) -> PyErr {
+    match err {
+        RustSearchError::InvalidThreshold(val) => {
+            SearchError::new_err(format!("Invalid threshold: {val}"))
+        }
+        other => SearchError::new_err(other.to_string()),
+    }
+}
+
+fn persist_error_to_py(err: RustPersistError) -> PyErr {
+    match err {
+        RustPersistError::Io(e) => PyErr::from(e),
+        other => pyo3::exceptions::PyValueError::new_err(other.to_string()),
+    }
 }
 
 #[pyclass(name = "CharacterNgrams")]
@@ -270,23 +321,22 @@ struct PyHashDb {
 impl PyHashDb {
     #[new]
     fn new(extractor: &Bound<'_, PyAny>) -> PyResult<Self> {
-        let py_feature_extractor =
-            if let Ok(char_ngram) = extractor.extract::<PyRef<PyCharacterNgrams>>() {
-                PyFeatureExtractor::Character(char_ngram.0.clone())
-            } else if let Ok(word_ngram) = extractor.extract::<PyRef<PyWordNgrams>>() {
-                PyFeatureExtractor::Word(word_ngram.0.clone())
-            } else if let Ok(custom) = extractor.extract::<PyRef<PyCustomExtractor>>() {
-                PyFeatureExtractor::Custom(custom.0.clone())
-            } else {
-                return Err(pyo3::exceptions::PyTypeError::new_err(
-                    "Extractor must be CharacterNgrams, WordNgrams, or CustomExtractor",
-                ));
-            };
-
+        let py_feature_extractor = to_py_feature_extractor(extractor)?;
         let db = HashDb::new(Arc::new(py_feature_extractor));
         Ok(Self { db })
     }
 
+    #[staticmethod]
+    fn load(path: &str, extractor: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let py_feature_extractor = to_py_feature_extractor(extractor)?;
+        let db = HashDb::load(path, Arc::new(py_feature_extractor)).map_err(persist_error_to_py)?;
+        Ok(Self { db })
+    }
+
+    fn save(&self, path: &str) -> PyResult<()> {
+        self.db.save(path).map_err(persist_error_to_py)
+    }
+
     fn insert(&mut self, text: String) {
         self.db.insert(text);
     }
@@ -295,8 +345,24 @@ impl PyHashDb {
         self.db.clear();
     }
 
+    fn remove(&mut self, id: usize) -> bool {
+        self.db.remove(id)
+    }
+
+    fn remove_str(&mut self, text: &str) -> usize {
+        self.db.remove_str(text)
+    }
+
+    fn update(&mut self, id: usize, new_text: String) -> bool {
+        self.db.update(id, new_text)
+    }
+
+    fn compact(&mut self) -> Vec<Option<usize>> {
+        self.db.compact()
+    }
+
     fn strings(&mut self) -> Vec<String> {
-        self.db.strings.clone()
+        self.db.iter().map(|(_, s)| s.to_string()).collect()
     }
 
     fn __len__(&self) -> usize {
@@ -343,11 +409,9 @@ impl PySearcher {
     ) -> PyResult<Vec<String>> {
         let db_borrow = self.db.borrow(py);
         let searcher = RustSearcher::new(&db_borrow.db, self.measure);
-        let results = searcher.search(query_string, alpha).map_err(|e| match e {
-            RustSearchError::InvalidThreshold(val) => {
-                SearchError::new_err(format!("Invalid threshold: {val}"))
-            }
-        })?;
+        let results = searcher
+            .search(query_string, alpha)
+            .map_err(search_error_to_py)?;
         Ok(results.into_iter().map(|s| s.to_string()).collect())
     }
 
@@ -361,16 +425,100 @@ impl PySearcher {
         let searcher = RustSearcher::new(&db_borrow.db, self.measure);
         let results = searcher
             .ranked_search(query_string, alpha)
-            .map_err(|e| match e {
-                RustSearchError::InvalidThreshold(val) => {
-                    SearchError::new_err(format!("Invalid threshold: {val}"))
-                }
-            })?;
+            .map_err(search_error_to_py)?;
+        Ok(results
+            .into_iter()
+            .map(|(s, score)| (s.to_string(), score))
+            .collect())
+    }
+
+    fn batch_search<'py>(
+        &self,
+        py: Python<'py>,
+        queries: Vec<String>,
+        alpha: f64,
+    ) -> PyResult<Vec<Vec<String>>> {
+        let db_borrow = self.db.borrow(py);
+        let searcher = RustSearcher::new(&db_borrow.db, self.measure);
+        let queries: Vec<&str> = queries.iter().map(String::as_str).collect();
+        let results = searcher
+            .batch_search(&queries, alpha)
+            .map_err(search_error_to_py)?;
+        Ok(results
+            .into_iter()
+            .map(|matches| matches.into_iter().map(str::to_string).collect())
+            .collect())
+    }
+
+    fn batch_ranked_search<'py>(
+        &self,
+        py: Python<'py>,
+        queries: Vec<String>,
+        alpha: f64,
+    ) -> PyResult<Vec<Vec<(String, f64)>>> {
+        let db_borrow = self.db.borrow(py);
+        let searcher = RustSearcher::new(&db_borrow.db, self.measure);
+        let queries: Vec<&str> = queries.iter().map(String::as_str).collect();
+        let results = searcher
+            .batch_ranked_search(&queries, alpha)
+            .map_err(search_error_to_py)?;
+        Ok(results
+            .into_iter()
+            .map(|matches| {
+                matches
+                    .into_iter()
+                    .map(|(s, score)| (s.to_string(), score))
+                    .collect()
+            })
+            .collect())
+    }
+
+    #[pyo3(signature = (query_string, k, floor_alpha=None))]
+    fn top_k<'py>(
+        &self,
+        py: Python<'py>,
+        query_string: &str,
+        k: usize,
+        floor_alpha: Option<f64>,
+    ) -> PyResult<Vec<(String, f64)>> {
+        let db_borrow = self.db.borrow(py);
+        let searcher = RustSearcher::new(&db_borrow.db, self.measure);
+        let results = match floor_alpha {
+            Some(floor_alpha) => searcher.top_k_with_floor(query_string, k, floor_alpha),
+            None => searcher.top_k(query_string, k),
+        }
+        .map_err(search_error_to_py)?;
         Ok(results
             .into_iter()
             .map(|(s, score)| (s.to_string(), score))
             .collect())
     }
+
+    fn search_ids<'py>(
+        &self,
+        py: Python<'py>,
+        query_string: &str,
+        alpha: f64,
+    ) -> PyResult<Vec<usize>> {
+        let db_borrow = self.db.borrow(py);
+        let searcher = RustSearcher::new(&db_borrow.db, self.measure);
+        searcher
+            .search_ids(query_string, alpha)
+            .map_err(search_error_to_py)
+    }
+
+    fn ranked_search_ids<'py>(
+        &self,
+        py: Python<'py>,
+        query_string: &str,
+        alpha: f64,
+    ) -> PyResult<Vec<(usize, f64)>> {
+        let db_borrow = self.db.borrow(py);
+        let searcher = RustSearcher::new(&db_borrow.db, self.measure);
+        searcher
+            .ranked_search_ids(query_string, alpha)
+            .map_err(search_error_to_py)
+    }
 }
 
 #[pymodule]
@@ -1,19 +1,83 @@
 use crate::database::StringId;
+use crate::distance::EditMeasure;
 use crate::measures::Measure;
 use crate::Database;
 use lasso::Spur;
 use rayon::prelude::*;
 use rustc_hash::{FxHashMap, FxHashSet};
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
 use thiserror::Error;
 
+/// Errors returned by searches. New variants may be added in minor releases.
 #[derive(Error, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum SearchError {
     #[error(
         "Search threshold alpha must be between 0.0 (exclusive) and 1.0 (inclusive), but was {0}"
     )]
     InvalidThreshold(f64),
+    #[error("Databases use incompatible feature extractors `{0}` and `{1}`")]
+    IncompatibleExtractors(String, String),
+    #[error("Database payloads are not of the requested type `{0}`")]
+    PayloadTypeMismatch(&'static str),
+    #[error("Database has no payload stored for string {0}")]
+    MissingPayload(StringId),
+    #[error("Verification parameter must be between 0.0 and 1.0 (inclusive), but was {0}")]
+    InvalidVerification(f64),
+    #[error("Frequency weight must be between 0.0 and 1.0 (inclusive), but was {0}")]
+    InvalidFrequencyWeight(f64),
 }
 
+/// Orders scores from highest to lowest, so every ranked result sorts the same way.
+/// Incomparable (NaN) scores compare equal and fall through to the caller's tie-break.
+pub(crate) fn by_score_desc(a: f64, b: f64) -> Ordering {
+    b.partial_cmp(&a).unwrap_or(Ordering::Equal)
+}
+
+pub(crate) fn check_threshold(alpha: f64) -> Result<(), SearchError> {
+    if !(alpha > 0.0 && alpha <= 1.0) {
+        return Err(SearchError::InvalidThreshold(alpha));
+    }
+    Ok(())
+}
+
+/// A second pass over n-gram matches, comparing them to the query character by character.
+///
+/// n-gram measures ignore where features occur, so a short query can score well against a
+/// long string containing it. Verification catches such matches without giving up the speed
+/// of the n-gram index, which still selects the candidates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verification {
+    /// Drops matches whose edit similarity to the query is below `min_similarity`, keeping
+    /// the n-gram scores of the others.
+    Filter {
+        measure: EditMeasure,
+        min_similarity: f64,
+    },
+    /// Scores matches as `(1 - weight) * n-gram score + weight * edit similarity`.
+    Blend { measure: EditMeasure, weight: f64 },
+}
+
+impl Verification {
+    fn check(&self) -> Result<(), SearchError> {
+        let value = match *self {
+            Verification::Filter { min_similarity, .. } => min_similarity,
+            Verification::Blend { weight, .. } => weight,
+        };
+        if !(0.0..=1.0).contains(&value) {
+            return Err(SearchError::InvalidVerification(value));
+        }
+        Ok(())
+    }
+}
+
+/// How much [`Searcher::top_k`] lowers the threshold between attempts.
+pub const TOP_K_ALPHA_STEP: f64 = 0.1;
+
+/// A candidate's id, string and similarity to the query.
+type ScoredCandidate<'a> = (StringId, &'a str, f64);
+
 pub struct Searcher<'db, M: Measure> {
     db: &'db dyn Database,
     measure: M,
@@ -24,6 +88,10 @@ impl<'db, M: Measure> Searcher<'db, M> {
         Self { db, measure }
     }
 
+    pub fn measure(&self) -> &M {
+        &self.measure
+    }
+
     pub fn search<'a>(
         &'a self,
         query_string: &str,
@@ -45,91 +113,461 @@ impl<'db, M: Measure> Searcher<'db, M> {
         query_string: &str,
         alpha: f64,
     ) -> Result<Vec<(&'a str, f64)>, SearchError> {
-        let (candidate_ids, query_features) = self.search_candidates(query_string, alpha)?;
+        let mut results_with_scores: Vec<(&'a str, f64)> = self
+            .scored_candidates(query_string, alpha)?
+            .into_iter()
+            .map(|(_, candidate_str, score)| (candidate_str, score))
+            .collect();
 
-        let mut results_with_scores: Vec<(&'a str, f64)> = candidate_ids
-            .par_iter()
-            .filter_map(|&id| {
-                if let (Some(candidate_str), Some(candidate_features)) =
-                    (self.db.get_string(id), self.db.get_features(id))
-                {
-                    let score = self.measure.similarity(&query_features, candidate_features);
-                    if score >= alpha {
-                        Some((candidate_str, score))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
+        results_with_scores
+            .sort_unstable_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.cmp(b.0)));
+
+        Ok(results_with_scores)
+    }
+
+    /// Like [`Searcher::ranked_search`], but passes the matches through `verification` before
+    /// ranking them.
+    ///
+    /// Candidates are still those scoring at least `alpha` under the n-gram measure; with
+    /// [`Verification::Blend`] the final scores may fall below it.
+    pub fn ranked_search_verified<'a>(
+        &'a self,
+        query_string: &str,
+        alpha: f64,
+        verification: &Verification,
+    ) -> Result<Vec<(&'a str, f64)>, SearchError> {
+        verification.check()?;
+        let mut results: Vec<(&'a str, f64)> = self
+            .scored_candidates(query_string, alpha)?
+            .into_par_iter()
+            .filter_map(|(_, candidate_str, score)| match *verification {
+                Verification::Filter {
+                    measure,
+                    min_similarity,
+                } => (measure.similarity(query_string, candidate_str) >= min_similarity)
+                    .then_some((candidate_str, score)),
+                Verification::Blend { measure, weight } => {
+                    let edit_similarity = measure.similarity(query_string, candidate_str);
+                    Some((
+                        candidate_str,
+                        (1.0 - weight) * score + weight * edit_similarity,
+                    ))
                 }
             })
             .collect();
 
-        results_with_scores.sort_unstable_by(|a, b| {
-            b.1.partial_cmp(&a.1)
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| a.0.cmp(b.0))
-        });
+        results.sort_unstable_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.cmp(b.0)));
 
-        Ok(results_with_scores)
+        Ok(results)
     }
 
-    fn search_candidates(
+    /// Like [`Searcher::search`], but returns the ids of the matches in ascending order.
+    ///
+    /// Unlike strings, ids distinguish duplicate strings inserted more than once.
+    pub fn search_ids(&self, query_string: &str, alpha: f64) -> Result<Vec<StringId>, SearchError> {
+        let (candidate_ids, _) = self.search_candidates(query_string, alpha)?;
+
+        let mut results: Vec<StringId> = candidate_ids
+            .into_iter()
+            .filter(|&id| self.db.get_string(id).is_some())
+            .collect();
+
+        results.sort_unstable();
+        Ok(results)
+    }
+
+    /// Like [`Searcher::ranked_search`], but returns the ids of the matches with their scores.
+    ///
+    /// Results are sorted by descending score, ties broken by ascending id.
+    pub fn ranked_search_ids(
         &self,
         query_string: &str,
         alpha: f64,
-    ) -> Result<(FxHashSet<StringId>, Vec<Spur>), SearchError> {
-        if !(alpha > 0.0 && alpha <= 1.0) {
-            return Err(SearchError::InvalidThreshold(alpha));
+    ) -> Result<Vec<(StringId, f64)>, SearchError> {
+        let mut results: Vec<(StringId, f64)> = self
+            .scored_candidates(query_string, alpha)?
+            .into_iter()
+            .map(|(id, _, score)| (id, score))
+            .collect();
+
+        results.sort_unstable_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.cmp(&b.0)));
+
+        Ok(results)
+    }
+
+    /// Like [`Searcher::ranked_search`], but also returns the id and payload of each match.
+    ///
+    /// `P` must be the payload type the database was built with, e.g. `HashDb<P>`. A match
+    /// without a stored payload, as in databases that do not store any, is an error.
+    pub fn ranked_search_with_payloads<'a, P: 'static>(
+        &'a self,
+        query_string: &str,
+        alpha: f64,
+    ) -> Result<Vec<(StringId, &'a str, &'a P, f64)>, SearchError> {
+        let mut results = self
+            .scored_candidates(query_string, alpha)?
+            .into_iter()
+            .map(|(id, candidate_str, score)| {
+                let payload = self
+                    .db
+                    .get_payload(id)
+                    .ok_or(SearchError::MissingPayload(id))?;
+                payload
+                    .downcast_ref::<P>()
+                    .map(|payload| (id, candidate_str, payload, score))
+                    .ok_or(SearchError::PayloadTypeMismatch(std::any::type_name::<P>()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        results.sort_unstable_by(|a, b| {
+            by_score_desc(a.3, b.3)
+                .then_with(|| a.1.cmp(b.1))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+
+        Ok(results)
+    }
+
+    /// Runs [`Searcher::search`] for every query, parallelizing across queries.
+    ///
+    /// Results are returned in the same order as `queries`.
+    pub fn batch_search<'a>(
+        &'a self,
+        queries: &[&str],
+        alpha: f64,
+    ) -> Result<Vec<Vec<&'a str>>, SearchError> {
+        check_threshold(alpha)?;
+        let batch_features = self.batch_query_features(queries);
+
+        Ok(batch_features
+            .par_iter()
+            .map(|query_features| {
+                let mut results: Vec<&'a str> = self
+                    .search_for_ids(query_features, alpha)
+                    .into_iter()
+                    .filter_map(|id| self.db.get_string(id))
+                    .collect();
+                results.sort_unstable();
+                results
+            })
+            .collect())
+    }
+
+    /// Runs [`Searcher::ranked_search`] for every query, parallelizing across queries.
+    ///
+    /// Results are returned in the same order as `queries`.
+    pub fn batch_ranked_search<'a>(
+        &'a self,
+        queries: &[&str],
+        alpha: f64,
+    ) -> Result<Vec<Vec<(&'a str, f64)>>, SearchError> {
+        Ok(self
+            .batch_scored_candidates(queries, alpha)?
+            .into_par_iter()
+            .map(|candidates| {
+                let mut results: Vec<(&'a str, f64)> = candidates
+                    .into_iter()
+                    .map(|(_, candidate_str, score)| (candidate_str, score))
+                    .collect();
+                results.sort_unstable_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.cmp(b.0)));
+                results
+            })
+            .collect())
+    }
+
+    /// Runs [`Searcher::ranked_search_ids`] for every query, parallelizing across queries.
+    ///
+    /// Results are returned in the same order as `queries`.
+    pub fn batch_ranked_search_ids(
+        &self,
+        queries: &[&str],
+        alpha: f64,
+    ) -> Result<Vec<Vec<(StringId, f64)>>, SearchError> {
+        Ok(self
+            .batch_scored_candidates(queries, alpha)?
+            .into_par_iter()
+            .map(|candidates| {
+                let mut results: Vec<(StringId, f64)> = candidates
+                    .into_iter()
+                    .map(|(id, _, score)| (id, score))
+                    .collect();
+                results
+                    .sort_unstable_by(|a, b| by_score_desc(a.1, b.1).then_with(|| a.0.cmp(&b.0)));
+                results
+            })
+            .collect())
+    }
+
+    /// Returns the `k` most similar strings, without having to pick a threshold up front.
+    ///
+    /// Equivalent to [`Searcher::top_k_with_floor`] with the smallest possible floor, so any
+    /// string sharing at least one feature with the query may be returned.
+    pub fn top_k<'a>(
+        &'a self,
+        query_string: &str,
+        k: usize,
+    ) -> Result<Vec<(&'a str, f64)>, SearchError> {
+        self.top_k_with_floor(query_string, k, f64::EPSILON)
+    }
+
+    /// Returns the `k` most similar strings scoring at least `floor_alpha`, ranked like
+    /// [`Searcher::ranked_search`].
+    ///
+    /// The search starts at alpha 1.0 and lowers the threshold in steps of
+    /// [`TOP_K_ALPHA_STEP`] until `k` matches are found or `floor_alpha` is reached. Every
+    /// string not found at a given threshold scores below it, so the result is exact. Each
+    /// candidate is scored only once, at the step that first finds it.
+    pub fn top_k_with_floor<'a>(
+        &'a self,
+        query_string: &str,
+        k: usize,
+        floor_alpha: f64,
+    ) -> Result<Vec<(&'a str, f64)>, SearchError> {
+        Ok(self
+            .top_k_matches(query_string, k, floor_alpha)?
+            .into_iter()
+            .map(|(_, candidate_str, score)| (candidate_str, score))
+            .collect())
+    }
+
+    /// Like [`Searcher::top_k`], but returns the ids of the matches with their scores.
+    pub fn top_k_ids(
+        &self,
+        query_string: &str,
+        k: usize,
+    ) -> Result<Vec<(StringId, f64)>, SearchError> {
+        self.top_k_ids_with_floor(query_string, k, f64::EPSILON)
+    }
+
+    /// Like [`Searcher::top_k_with_floor`], but returns the ids of the matches with their
+    /// scores, in the same order.
+    pub fn top_k_ids_with_floor(
+        &self,
+        query_string: &str,
+        k: usize,
+        floor_alpha: f64,
+    ) -> Result<Vec<(StringId, f64)>, SearchError> {
+        Ok(self
+            .top_k_matches(query_string, k, floor_alpha)?
+            .into_iter()
+            .map(|(id, _, score)| (id, score))
+            .collect())
+    }
+
+    fn top_k_matches<'a>(
+        &'a self,
+        query_string: &str,
+        k: usize,
+        floor_alpha: f64,
+    ) -> Result<Vec<ScoredCandidate<'a>>, SearchError> {
+        check_threshold(floor_alpha)?;
+        if k == 0 {
+            return Ok(Vec::new());
         }
 
-        // Extract features with minimal lock time
-        let query_features = {
-            let interner_arc = self.db.interner();
-            let mut interner = interner_arc.lock().unwrap();
-            let extractor = self.db.feature_extractor();
-            extractor.features(query_string, &mut interner)
+        let query_features = self.query_features(query_string);
+        // Scores of every candidate found so far, including those below the current threshold
+        let mut scored: FxHashMap<StringId, (&'a str, f64)> = FxHashMap::default();
+        let mut step = 0;
+        let alpha = loop {
+            let alpha = (1.0 - TOP_K_ALPHA_STEP * step as f64).max(floor_alpha);
+            let new_ids: FxHashSet<StringId> = self
+                .search_for_ids(&query_features, alpha)
+                .into_iter()
+                .filter(|id| !scored.contains_key(id))
+                .collect();
+            for (id, candidate_str, score) in self.score_candidates(&new_ids, &query_features, 0.0)
+            {
+                scored.insert(id, (candidate_str, score));
+            }
+
+            let matches = scored
+                .values()
+                .filter(|&&(_, score)| score >= alpha)
+                .count();
+            if matches >= k || alpha <= floor_alpha {
+                break alpha;
+            }
+            step += 1;
         };
 
+        let mut results: Vec<ScoredCandidate<'a>> = scored
+            .into_iter()
+            .filter(|&(_, (_, score))| score >= alpha)
+            .map(|(id, (candidate_str, score))| (id, candidate_str, score))
+            .collect();
+
+        results.sort_unstable_by(|a, b| {
+            by_score_desc(a.2, b.2)
+                .then_with(|| a.1.cmp(b.1))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        results.truncate(k);
+        Ok(results)
+    }
+
+    /// Returns every candidate whose similarity to the query is at least `alpha`.
+    fn scored_candidates<'a>(
+        &'a self,
+        query_string: &str,
+        alpha: f64,
+    ) -> Result<Vec<ScoredCandidate<'a>>, SearchError> {
+        let (candidate_ids, query_features) = self.search_candidates(query_string, alpha)?;
+        Ok(self.score_candidates(&candidate_ids, &query_features, alpha))
+    }
+
+    /// Returns the scored candidates of every query, in the same order as `queries`.
+    fn batch_scored_candidates<'a>(
+        &'a self,
+        queries: &[&str],
+        alpha: f64,
+    ) -> Result<Vec<Vec<ScoredCandidate<'a>>>, SearchError> {
+        check_threshold(alpha)?;
+        let batch_features = self.batch_query_features(queries);
+
+        Ok(batch_features
+            .par_iter()
+            .map(|query_features| {
+                let candidate_ids = self.search_for_ids(query_features, alpha);
+                self.score_candidates(&candidate_ids, query_features, alpha)
+            })
+            .collect())
+    }
+
+    fn score_candidates<'a>(
+        &'a self,
+        candidate_ids: &FxHashSet<StringId>,
+        query_features: &[Spur],
+        alpha: f64,
+    ) -> Vec<ScoredCandidate<'a>> {
+        candidate_ids
+            .par_iter()
+            .filter_map(|&id| self.score_candidate(id, query_features, alpha))
+            .collect()
+    }
+
+    /// Scores the candidates of `query_features` on the calling thread only.
+    ///
+    /// For callers holding a lock, which must not run rayon work: a thread waiting for that
+    /// work may steal a task that needs the same lock.
+    pub(crate) fn sequential_scored_candidates<'a>(
+        &'a self,
+        query_features: &[Spur],
+        alpha: f64,
+    ) -> Vec<ScoredCandidate<'a>> {
+        if query_features.is_empty() {
+            return Vec::new();
+        }
+
+        let candidate_ids: FxHashSet<StringId> = self
+            .candidate_sizes(query_features.len(), alpha)
+            .flat_map(|size| self.candidates_of_size(query_features, alpha, size, None))
+            .collect();
+        candidate_ids
+            .into_iter()
+            .filter_map(|id| self.score_candidate(id, query_features, alpha))
+            .collect()
+    }
+
+    fn score_candidate<'a>(
+        &'a self,
+        id: StringId,
+        query_features: &[Spur],
+        alpha: f64,
+    ) -> Option<ScoredCandidate<'a>> {
+        let candidate_str = self.db.get_string(id)?;
+        let candidate_features = self.db.get_features(id)?;
+        let score = self.measure.similarity(query_features, candidate_features);
+        (score >= alpha).then_some((id, candidate_str, score))
+    }
+
+    fn search_candidates(
+        &self,
+        query_string: &str,
+        alpha: f64,
+    ) -> Result<(FxHashSet<StringId>, Vec<Spur>), SearchError> {
+        check_threshold(alpha)?;
+
+        let query_features = self.query_features(query_string);
         let candidate_ids = self.search_for_ids(&query_features, alpha);
         Ok((candidate_ids, query_features))
     }
 
-    fn search_for_ids(&self, query_features: &[Spur], alpha: f64) -> FxHashSet<StringId> {
-        let query_size = query_features.len();
-        if query_size == 0 {
-            return FxHashSet::default();
-        }
+    fn query_features(&self, query_string: &str) -> Vec<Spur> {
+        self.db.query_features(query_string)
+    }
 
-        let min_feat_size = self.measure.min_feature_size(query_size, alpha);
-        let max_feat_size = self.measure.max_feature_size(query_size, alpha, self.db);
+    pub(crate) fn batch_query_features(&self, queries: &[&str]) -> Vec<Vec<Spur>> {
+        self.db.batch_query_features(queries)
+    }
 
-        (min_feat_size..=max_feat_size)
-            .into_par_iter()
-            .map(|candidate_size| {
-                let tau =
-                    self.measure
-                        .minimum_common_feature_count(query_size, candidate_size, alpha);
+    pub(crate) fn search_for_ids(
+        &self,
+        query_features: &[Spur],
+        alpha: f64,
+    ) -> FxHashSet<StringId> {
+        self.search_for_ids_after(query_features, alpha, None)
+    }
 
-                if tau == 0 || tau > query_size {
-                    return FxHashSet::default();
-                }
+    /// Like [`Searcher::search_for_ids`], but only considers ids greater than `after`, so
+    /// other ids are never counted as candidates.
+    pub(crate) fn search_for_ids_after(
+        &self,
+        query_features: &[Spur],
+        alpha: f64,
+        after: Option<StringId>,
+    ) -> FxHashSet<StringId> {
+        if query_features.is_empty() {
+            return FxHashSet::default();
+        }
 
-                self.overlap_join(query_features, tau, candidate_size)
-                    .into_iter()
-                    .collect::<FxHashSet<StringId>>()
-            })
+        self.candidate_sizes(query_features.len(), alpha)
+            .into_par_iter()
+            .map(|size| self.candidates_of_size(query_features, alpha, size, after))
             .reduce(FxHashSet::default, |mut acc, set| {
                 acc.extend(set);
                 acc
             })
     }
 
+    /// Feature-set sizes a string needs to be able to reach `alpha` against the query.
+    /// The query must have at least one feature.
+    fn candidate_sizes(&self, query_size: usize, alpha: f64) -> RangeInclusive<usize> {
+        let min_feat_size = self.measure.min_feature_size(query_size, alpha);
+        // Not every measure bounds the size by the database, which matters for tiny alphas
+        let max_feat_size = self
+            .measure
+            .max_feature_size(query_size, alpha, self.db)
+            .min(self.db.max_feature_len());
+        min_feat_size..=max_feat_size
+    }
+
+    fn candidates_of_size(
+        &self,
+        query_features: &[Spur],
+        alpha: f64,
+        candidate_size: usize,
+        after: Option<StringId>,
+    ) -> FxHashSet<StringId> {
+        let query_size = query_features.len();
+        let tau = self
+            .measure
+            .minimum_common_feature_count(query_size, candidate_size, alpha);
+        if tau == 0 || tau > query_size {
+            return FxHashSet::default();
+        }
+
+        self.overlap_join(query_features, tau, candidate_size, after)
+            .into_iter()
+            .collect()
+    }
+
     fn overlap_join(
         &self,
         query_features: &[Spur],
         tau: usize,
         candidate_size: usize,
+        after: Option<StringId>,
     ) -> Vec<StringId> {
         if query_features.is_empty() || tau == 0 {
             return Vec::new();
@@ -157,7 +595,9 @@ impl<'db, M: Measure> Searcher<'db, M> {
         for &idx in &feature_indices[..q_len.saturating_sub(tau) + 1] {
             if let Some(ids) = feature_sets[idx] {
                 for &id in ids {
-                    *candidate_counts.entry(id).or_insert(0) += 1;
+                    if after.is_none_or(|after| id > after) {
+                        *candidate_counts.entry(id).or_insert(0) += 1;
+                    }
                 }
             }
         }
@@ -0,0 +1,265 @@
+//! A small HTTP/JSON search service, enabled with the `server` feature.
+//!
+//! Every endpoint takes a JSON `POST` body and answers with JSON:
+//!
+//! | Endpoint         | Request                                          | Response                        |
+//! |------------------|--------------------------------------------------|---------------------------------|
+//! | `/search`        | `{"query": "..", "threshold": 0.7}`              | `{"results": [".."]}`           |
+//! | `/ranked_search` | `{"query": "..", "threshold": 0.7}`              | `{"results": [{"id", "string", "score"}]}` |
+//! | `/top_k`         | `{"query": "..", "k": 5, "floor": 0.1}`          | `{"results": [{"id", "string", "score"}]}` |
+//! | `/batch`         | `{"queries": [".."], "threshold": 0.7, "ranked": false}` | `{"results": [[..]]}`   |
+//! | `/insert`        | `{"strings": [".."]}`                            | `{"ids": [0]}`                  |
+//!
+//! `threshold` falls back to the configured default and `floor` is optional. Errors are
+//! reported as `{"error": ".."}` with a 4xx status.
+
+use crate::database::{Database, StringId};
+use crate::measures::{Cosine, Dice, ExactMatch, Jaccard, Measure, Overlap};
+use crate::search::{SearchError, Searcher};
+use crate::HashDb;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::str::FromStr;
+use std::sync::RwLock;
+use tiny_http::{Header, Request, Response};
+
+/// The similarity measure a server searches with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasureKind {
+    Cosine,
+    Dice,
+    Jaccard,
+    Overlap,
+    Exact,
+}
+
+impl FromStr for MeasureKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "cosine" => Ok(Self::Cosine),
+            "dice" => Ok(Self::Dice),
+            "jaccard" => Ok(Self::Jaccard),
+            "overlap" => Ok(Self::Overlap),
+            "exact" => Ok(Self::Exact),
+            other => Err(format!("unknown similarity measure `{other}`")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub measure: MeasureKind,
+    /// Threshold used by requests that do not specify one.
+    pub threshold: f64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            measure: MeasureKind::Cosine,
+            threshold: 0.7,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchRequest {
+    query: String,
+    threshold: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct TopKRequest {
+    query: String,
+    k: usize,
+    floor: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct BatchRequest {
+    queries: Vec<String>,
+    threshold: Option<f64>,
+    #[serde(default)]
+    ranked: bool,
+}
+
+#[derive(Deserialize)]
+struct InsertRequest {
+    strings: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Match<'a> {
+    id: StringId,
+    string: &'a str,
+    score: f64,
+}
+
+/// A JSON response body and its HTTP status code.
+pub type JsonResponse = (u16, serde_json::Value);
+
+fn error(status: u16, message: impl ToString) -> JsonResponse {
+    (status, json!({ "error": message.to_string() }))
+}
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, JsonResponse> {
+    serde_json::from_str(body).map_err(|e| error(400, format!("invalid request body: {e}")))
+}
+
+fn search_error(e: SearchError) -> JsonResponse {
+    error(400, e)
+}
+
+fn matches<'a>(db: &'a dyn Database, results: Vec<(StringId, f64)>) -> Vec<Match<'a>> {
+    results
+        .into_iter()
+        .filter_map(|(id, score)| db.get_string(id).map(|string| Match { id, string, score }))
+        .collect()
+}
+
+/// Serves searches against a [`HashDb`] that can also grow through `/insert`.
+///
+/// Searches share a read lock on the database; an insert briefly takes the write lock.
+pub struct SearchServer {
+    db: RwLock<HashDb>,
+    config: ServerConfig,
+}
+
+impl SearchServer {
+    pub fn new(db: HashDb, config: ServerConfig) -> Self {
+        Self {
+            db: RwLock::new(db),
+            config,
+        }
+    }
+
+    /// Handles a single request, independent of any transport.
+    pub fn handle(&self, method: &str, path: &str, body: &str) -> JsonResponse {
+        if !matches!(
+            path,
+            "/search" | "/ranked_search" | "/top_k" | "/batch" | "/insert"
+        ) {
+            return error(404, format!("unknown endpoint `{path}`"));
+        }
+        if method != "POST" {
+            return error(405, "endpoints only accept POST requests");
+        }
+
+        let result = if path == "/insert" {
+            self.insert(body)
+        } else {
+            match self.config.measure {
+                MeasureKind::Cosine => self.search(Cosine, path, body),
+                MeasureKind::Dice => self.search(Dice, path, body),
+                MeasureKind::Jaccard => self.search(Jaccard, path, body),
+                MeasureKind::Overlap => self.search(Overlap, path, body),
+                MeasureKind::Exact => self.search(ExactMatch, path, body),
+            }
+        };
+        result.unwrap_or_else(|e| e)
+    }
+
+    fn search<M: Measure>(
+        &self,
+        measure: M,
+        path: &str,
+        body: &str,
+    ) -> Result<JsonResponse, JsonResponse> {
+        let db = self.db.read().unwrap();
+        let searcher = Searcher::new(&*db, measure);
+        let default_threshold = self.config.threshold;
+
+        let results = match path {
+            "/search" => {
+                let request: SearchRequest = parse(body)?;
+                let threshold = request.threshold.unwrap_or(default_threshold);
+                json!(searcher
+                    .search(&request.query, threshold)
+                    .map_err(search_error)?)
+            }
+            "/ranked_search" => {
+                let request: SearchRequest = parse(body)?;
+                let threshold = request.threshold.unwrap_or(default_threshold);
+                let results = searcher
+                    .ranked_search_ids(&request.query, threshold)
+                    .map_err(search_error)?;
+                json!(matches(&*db, results))
+            }
+            "/top_k" => {
+                let request: TopKRequest = parse(body)?;
+                let results = match request.floor {
+                    Some(floor) => searcher.top_k_ids_with_floor(&request.query, request.k, floor),
+                    None => searcher.top_k_ids(&request.query, request.k),
+                }
+                .map_err(search_error)?;
+                json!(matches(&*db, results))
+            }
+            _ => {
+                let request: BatchRequest = parse(body)?;
+                let threshold = request.threshold.unwrap_or(default_threshold);
+                let queries: Vec<&str> = request.queries.iter().map(String::as_str).collect();
+                if request.ranked {
+                    let results: Vec<Vec<Match>> = searcher
+                        .batch_ranked_search_ids(&queries, threshold)
+                        .map_err(search_error)?
+                        .into_iter()
+                        .map(|results| matches(&*db, results))
+                        .collect();
+                    json!(results)
+                } else {
+                    json!(searcher
+                        .batch_search(&queries, threshold)
+                        .map_err(search_error)?)
+                }
+            }
+        };
+        Ok((200, json!({ "results": results })))
+    }
+
+    fn insert(&self, body: &str) -> Result<JsonResponse, JsonResponse> {
+        let request: InsertRequest = parse(body)?;
+        let mut db = self.db.write().unwrap();
+        let first_id = db.strings.len();
+        let ids: Vec<StringId> = (first_id..first_id + request.strings.len()).collect();
+        for string in request.strings {
+            db.insert(string);
+        }
+        Ok((200, json!({ "ids": ids })))
+    }
+
+    /// Answers requests arriving at `http` on `num_workers` threads until
+    /// [`tiny_http::Server::unblock`] is called.
+    pub fn serve(&self, http: &tiny_http::Server, num_workers: usize) {
+        std::thread::scope(|scope| {
+            for _ in 0..num_workers.max(1) {
+                scope.spawn(|| {
+                    while let Ok(request) = http.recv() {
+                        self.respond(request);
+                    }
+                });
+            }
+        });
+    }
+
+    fn respond(&self, mut request: Request) {
+        let mut body = String::new();
+        let (status, value) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => {
+                // Ignore any query string
+                let path = request.url().split('?').next().unwrap_or_default();
+                self.handle(request.method().as_str(), path, &body)
+            }
+            Err(e) => error(400, format!("could not read request body: {e}")),
+        };
+
+        let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(content_type);
+        // The client may already have disconnected; there is nobody left to tell
+        let _ = request.respond(response);
+    }
+}
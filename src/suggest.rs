@@ -0,0 +1,131 @@
+use crate::database::{Database, StringId};
+use crate::distance::levenshtein;
+use crate::measures::Measure;
+use crate::search::{by_score_desc, SearchError, Searcher};
+
+#[derive(Debug, Clone)]
+pub struct SuggesterOptions {
+    /// Minimum n-gram similarity for a word to be considered.
+    pub alpha: f64,
+    /// Words further than this many edits from the input are dropped.
+    pub max_distance: Option<usize>,
+    /// Share of the final score given to the word frequency, between 0 and 1; other values
+    /// make [`Suggester::new`] fail.
+    pub frequency_weight: f64,
+    pub max_suggestions: usize,
+}
+
+impl Default for SuggesterOptions {
+    fn default() -> Self {
+        Self {
+            alpha: 0.5,
+            max_distance: Some(2),
+            frequency_weight: 0.2,
+            max_suggestions: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion<'a> {
+    pub id: StringId,
+    pub word: &'a str,
+    /// Frequency stored as the word's payload.
+    pub frequency: u64,
+    /// n-gram similarity to the input under the searcher's measure.
+    pub similarity: f64,
+    /// Levenshtein distance to the input.
+    pub distance: usize,
+    pub score: f64,
+}
+
+/// Suggests spelling corrections from a dictionary of words and their frequencies.
+///
+/// The dictionary is a database whose payloads are `u64` frequencies, e.g. a `HashDb<u64>`
+/// filled with [`HashDb::insert_with_payload`](crate::HashDb::insert_with_payload). Words are
+/// retrieved with [`Searcher::ranked_search_with_payloads`] and scored as
+///
+/// ```text
+/// (1 - frequency_weight) * (similarity + edit similarity) / 2
+///     + frequency_weight * ln(1 + frequency) / ln(1 + highest candidate frequency)
+/// ```
+///
+/// where the edit similarity is the Levenshtein distance scaled to `[0, 1]`.
+pub struct Suggester<'db, M: Measure> {
+    searcher: Searcher<'db, M>,
+    options: SuggesterOptions,
+}
+
+impl<'db, M: Measure> Suggester<'db, M> {
+    pub fn new(
+        db: &'db dyn Database,
+        measure: M,
+        options: SuggesterOptions,
+    ) -> Result<Self, SearchError> {
+        let weight = options.frequency_weight;
+        if !(0.0..=1.0).contains(&weight) {
+            return Err(SearchError::InvalidFrequencyWeight(weight));
+        }
+        Ok(Self {
+            searcher: Searcher::new(db, measure),
+            options,
+        })
+    }
+
+    /// Returns up to `max_suggestions` corrections for `word`, best first.
+    ///
+    /// Ties are broken by higher frequency, then alphabetically.
+    pub fn suggest<'a>(&'a self, word: &str) -> Result<Vec<Suggestion<'a>>, SearchError> {
+        let weight = self.options.frequency_weight;
+        let word_len = word.chars().count();
+        let candidates = self
+            .searcher
+            .ranked_search_with_payloads::<u64>(word, self.options.alpha)?;
+
+        let max_frequency = candidates
+            .iter()
+            .map(|&(_, _, &frequency, _)| frequency)
+            .max()
+            .unwrap_or(0);
+        let frequency_scale = (1.0 + max_frequency as f64).ln();
+
+        let mut suggestions: Vec<Suggestion<'a>> = candidates
+            .into_iter()
+            .filter_map(|(id, candidate, &frequency, similarity)| {
+                let distance = levenshtein(word, candidate);
+                if self.options.max_distance.is_some_and(|max| distance > max) {
+                    return None;
+                }
+
+                let max_len = word_len.max(candidate.chars().count());
+                let edit_similarity = if max_len == 0 {
+                    1.0
+                } else {
+                    1.0 - distance as f64 / max_len as f64
+                };
+                let relevance = (similarity + edit_similarity) / 2.0;
+                let popularity = if frequency_scale > 0.0 {
+                    (1.0 + frequency as f64).ln() / frequency_scale
+                } else {
+                    0.0
+                };
+                Some(Suggestion {
+                    id,
+                    word: candidate,
+                    frequency,
+                    similarity,
+                    distance,
+                    score: (1.0 - weight) * relevance + weight * popularity,
+                })
+            })
+            .collect();
+
+        suggestions.sort_by(|a, b| {
+            by_score_desc(a.score, b.score)
+                .then_with(|| b.frequency.cmp(&a.frequency))
+                .then_with(|| a.word.cmp(b.word))
+        });
+        suggestions.truncate(self.options.max_suggestions);
+        Ok(suggestions)
+    }
+}
@@ -0,0 +1,177 @@
+use crate::database::{Database, StringId};
+use crate::measures::Measure;
+use crate::search::{by_score_desc, check_threshold, SearchError, Searcher};
+use rayon::prelude::*;
+
+/// How overlapping matches are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Prefer matches spanning more tokens, then higher scores.
+    LongestFirst,
+    /// Prefer higher scores, then matches spanning more tokens.
+    BestScoreFirst,
+}
+
+#[derive(Debug, Clone)]
+pub struct TaggerOptions {
+    /// Longest token window that is looked up.
+    pub max_window: usize,
+    /// Minimum similarity for a window to match.
+    pub alpha: f64,
+    pub resolution: Resolution,
+}
+
+impl Default for TaggerOptions {
+    fn default() -> Self {
+        Self {
+            max_window: 5,
+            alpha: 0.7,
+            resolution: Resolution::LongestFirst,
+        }
+    }
+}
+
+/// A span of the tagged text matching a database string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag<'db> {
+    /// Character offsets of the span, end exclusive.
+    pub start: usize,
+    pub end: usize,
+    /// Byte offsets of the span, for slicing the tagged text.
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub id: StringId,
+    pub matched: &'db str,
+    pub score: f64,
+}
+
+/// A candidate window and its best match, before overlap resolution.
+struct Candidate {
+    first_token: usize,
+    num_tokens: usize,
+    byte_start: usize,
+    byte_end: usize,
+    id: StringId,
+    score: f64,
+}
+
+/// Finds spans of running text that approximately match strings of a database, like
+/// QuickUMLS does for medical concepts.
+///
+/// Text is split into tokens (runs of alphanumeric characters), and every window of up to
+/// `max_window` consecutive tokens is looked up. Among overlapping matching windows only the
+/// preferred one, according to [`Resolution`], is kept.
+pub struct Tagger<'db, M: Measure> {
+    searcher: Searcher<'db, M>,
+    db: &'db dyn Database,
+    options: TaggerOptions,
+}
+
+impl<'db, M: Measure> Tagger<'db, M> {
+    pub fn new(db: &'db dyn Database, measure: M, options: TaggerOptions) -> Self {
+        Self {
+            searcher: Searcher::new(db, measure),
+            db,
+            options,
+        }
+    }
+
+    /// Returns the non-overlapping matches in `text`, ordered by position.
+    pub fn tag(&self, text: &str) -> Result<Vec<Tag<'db>>, SearchError> {
+        check_threshold(self.options.alpha)?;
+        let tokens = tokenize(text);
+
+        let windows: Vec<(usize, usize)> = (0..tokens.len())
+            .flat_map(|first| {
+                let max_len = self.options.max_window.min(tokens.len() - first);
+                (1..=max_len).map(move |len| (first, len))
+            })
+            .collect();
+
+        let mut candidates: Vec<Candidate> = windows
+            .into_par_iter()
+            .map(|(first_token, num_tokens)| {
+                let byte_start = tokens[first_token].0;
+                let byte_end = tokens[first_token + num_tokens - 1].1;
+                let matches = self
+                    .searcher
+                    .ranked_search_ids(&text[byte_start..byte_end], self.options.alpha)?;
+                // Matches are sorted by descending score, ties broken by ascending id
+                Ok(matches.first().map(|&(id, score)| Candidate {
+                    first_token,
+                    num_tokens,
+                    byte_start,
+                    byte_end,
+                    id,
+                    score,
+                }))
+            })
+            .collect::<Result<Vec<_>, SearchError>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let by_length = |a: &Candidate, b: &Candidate| b.num_tokens.cmp(&a.num_tokens);
+        let by_score = |a: &Candidate, b: &Candidate| by_score_desc(a.score, b.score);
+        candidates.sort_by(|a, b| {
+            match self.options.resolution {
+                Resolution::LongestFirst => by_length(a, b).then_with(|| by_score(a, b)),
+                Resolution::BestScoreFirst => by_score(a, b).then_with(|| by_length(a, b)),
+            }
+            .then_with(|| a.first_token.cmp(&b.first_token))
+        });
+
+        let mut taken = vec![false; tokens.len()];
+        let mut selected = Vec::new();
+        for candidate in candidates {
+            let span = candidate.first_token..candidate.first_token + candidate.num_tokens;
+            if taken[span.clone()].iter().any(|&t| t) {
+                continue;
+            }
+            taken[span].iter_mut().for_each(|t| *t = true);
+            selected.push(candidate);
+        }
+        selected.sort_by_key(|candidate| candidate.first_token);
+
+        Ok(selected
+            .into_iter()
+            .filter_map(|candidate| {
+                let matched = self.db.get_string(candidate.id)?;
+                let start = text[..candidate.byte_start].chars().count();
+                let end = start
+                    + text[candidate.byte_start..candidate.byte_end]
+                        .chars()
+                        .count();
+                Some(Tag {
+                    start,
+                    end,
+                    byte_start: candidate.byte_start,
+                    byte_end: candidate.byte_end,
+                    id: candidate.id,
+                    matched,
+                    score: candidate.score,
+                })
+            })
+            .collect())
+    }
+}
+
+/// Returns the byte ranges of the runs of alphanumeric characters in `text`.
+fn tokenize(text: &str) -> Vec<(usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, ch) in text.char_indices() {
+        match (ch.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                tokens.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((s, text.len()));
+    }
+    tokens
+}
@@ -141,3 +141,58 @@ class TestSimstringBindings:
     def test_ranked_search_error_on_invalid_threshold(self):
         with pytest.raises(SearchError, match=r"Invalid threshold: 1\.1"):
             self.searcher.ranked_search("test", 1.1)
+
+    def test_save_and_load(self, tmp_path):
+        path = str(tmp_path / "names.db")
+        self.db.save(path)
+
+        loaded = HashDb.load(path, CharacterNgrams(n=2, endmarker="$"))
+        assert loaded.strings() == self.db.strings()
+
+        searcher = Searcher(loaded, Cosine())
+        assert searcher.search("apple", 0.8) == ["apple"]
+
+    def test_load_with_mismatched_extractor(self, tmp_path):
+        path = str(tmp_path / "names.db")
+        self.db.save(path)
+
+        with pytest.raises(ValueError, match="Feature extractor mismatch"):
+            HashDb.load(path, CharacterNgrams(n=3, endmarker="$"))
+
+    def test_remove_update_and_compact(self):
+        assert self.db.remove(0)
+        assert not self.db.remove(0)
+        assert len(self.db) == 2
+        assert self.searcher.search("apply", 0.8) == []
+
+        assert self.db.update(1, "apricot")
+        assert self.searcher.search("apricot", 0.8) == ["apricot"]
+
+        assert self.db.remove_str("banana") == 1
+        assert self.db.compact() == [None, 0, None]
+        assert self.db.strings() == ["apricot"]
+
+    def test_search_ids(self):
+        self.db.insert("apple")
+        assert self.searcher.search_ids("apple", 0.8) == [1, 3]
+
+        ranked = self.searcher.ranked_search_ids("apple", 0.6)
+        assert [id for id, _ in ranked] == [1, 3, 0]
+        assert ranked[0][1] == pytest.approx(1.0)
+
+    def test_top_k(self):
+        results = self.searcher.top_k("apple", 2)
+        assert [s for s, _ in results] == ["apple", "apply"]
+        assert results[0][1] == pytest.approx(1.0)
+
+        assert self.searcher.top_k("apple", 5, floor_alpha=0.8) == [("apple", pytest.approx(1.0))]
+
+    def test_batch_search(self):
+        queries = ["banana", "apple", "zzz"]
+        assert self.searcher.batch_search(queries, 0.8) == [["banana"], ["apple"], []]
+
+        ranked = self.searcher.batch_ranked_search(queries, 0.6)
+        assert [[s for s, _ in r] for r in ranked] == [["banana"], ["apple", "apply"], []]
+
+        with pytest.raises(SearchError, match=r"Invalid threshold: 1\.1"):
+            self.searcher.batch_search(queries, 1.1)
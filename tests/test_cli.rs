@@ -0,0 +1,113 @@
+use simstring_rust::database::simstring_cpp;
+use simstring_rust::Database;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+fn temp_db_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "simstring_rust_cli_{}_{}",
+        std::process::id(),
+        name
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join("names.db")
+}
+
+fn simstring(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_simstring"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout_lines(output: &Output) -> Vec<String> {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone())
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_cli_build_and_query() {
+    let path = temp_db_path("char");
+    let db = path.to_str().unwrap();
+
+    let built = simstring(
+        &["-b", "-d", db, "-q"],
+        "Barack Obama\nBarack H. Obama\nJoe Biden\n",
+    );
+    assert!(stdout_lines(&built).is_empty());
+    // The database is written in the C++ layout
+    assert_eq!(simstring_cpp::read(&path).unwrap().total_strings(), 3);
+
+    let queried = simstring(
+        &["-d", db, "-s", "cosine", "-t", "0.6", "-e"],
+        "Barack Obama\n",
+    );
+    let lines = stdout_lines(&queried);
+    assert_eq!(
+        lines[..3],
+        ["Barack Obama", "\tBarack H. Obama", "\tBarack Obama"]
+    );
+    assert!(lines[3].starts_with("2 strings retrieved"));
+
+    let ranked = simstring(&["-d", db, "--threshold=0.6", "-r", "-q"], "Barack Obama\n");
+    assert_eq!(
+        stdout_lines(&ranked)[0],
+        "\tBarack Obama\t1.000000".to_string()
+    );
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_cli_word_mode() {
+    let path = temp_db_path("word");
+    let db = path.to_str().unwrap();
+
+    simstring(
+        &["-b", "-w", "-n2", "-d", db],
+        "new york city\nnew jersey\nyork\n",
+    );
+    let queried = simstring(
+        &["-d", db, "-w", "-n", "2", "-s", "dice", "-t", "0.5", "-q"],
+        "new york\n",
+    );
+    assert_eq!(stdout_lines(&queried), ["\tnew york city"]);
+
+    // The n-gram size is read back from the database rather than from `-n`
+    let queried = simstring(
+        &["-d", db, "-w", "-s", "dice", "-t", "0.5", "-q"],
+        "new york\n",
+    );
+    assert_eq!(stdout_lines(&queried), ["\tnew york city"]);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_cli_rejects_bad_arguments() {
+    let output = simstring(&["-d", "missing.db", "-s", "levenshtein"], "");
+    assert!(!output.status.success());
+
+    let output = simstring(&["-t", "0.5"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("-d"));
+}
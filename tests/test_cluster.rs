@@ -0,0 +1,91 @@
+use simstring_rust::cluster::{connected_components, leader_clusters, Cluster};
+use simstring_rust::join::self_join;
+use simstring_rust::{CharacterNgrams, Cosine, Database, HashDb, SearchError};
+use std::sync::Arc;
+
+fn db_from(strings: &[&str]) -> HashDb {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in strings {
+        db.insert(s.to_string());
+    }
+    db
+}
+
+fn vendor_db() -> HashDb {
+    db_from(&[
+        "Acme Inc",
+        "Globex Corp",
+        "Acme Inc.",
+        "Acme  Inc",
+        "Globex Corp.",
+        "Umbrella",
+    ])
+}
+
+fn members(clusters: &[Cluster]) -> Vec<Vec<usize>> {
+    clusters.iter().map(|c| c.members.clone()).collect()
+}
+
+#[test]
+fn test_connected_components_cover_every_string() {
+    let db = vendor_db();
+    let clusters = connected_components(&db, Cosine, 0.7).unwrap();
+
+    assert_eq!(members(&clusters), vec![vec![0, 2, 3], vec![1, 4], vec![5]]);
+    let mut ids: Vec<usize> = clusters.iter().flat_map(|c| c.members.clone()).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, (0..db.total_strings()).collect::<Vec<_>>());
+    // "Acme Inc" is close to both of its variants, so it is the most central member
+    assert_eq!(clusters[0].representative, 0);
+    assert_eq!(clusters[2].representative, 5);
+}
+
+#[test]
+fn test_connected_components_are_transitive() {
+    // Each string is close to its neighbours but the ends of the chain are not
+    let db = db_from(&[
+        "abcdefghijkl",
+        "abcdefghijkx",
+        "abcdefghijyx",
+        "abcdefghizyx",
+    ]);
+    let pairs = self_join(&db, Cosine, 0.7).unwrap();
+    assert!(pairs.iter().all(|&(a, b, _)| (a, b) != (0, 3)));
+
+    let clusters = connected_components(&db, Cosine, 0.7).unwrap();
+    assert_eq!(members(&clusters), vec![vec![0, 1, 2, 3]]);
+
+    let clusters = leader_clusters(&db, Cosine, 0.7).unwrap();
+    assert_eq!(members(&clusters), vec![vec![0, 1, 2], vec![3]]);
+    assert_eq!(clusters[1].representative, 3);
+}
+
+#[test]
+fn test_leader_clusters_join_best_leader() {
+    let db = vendor_db();
+    let clusters = leader_clusters(&db, Cosine, 0.7).unwrap();
+
+    assert_eq!(members(&clusters), vec![vec![0, 2, 3], vec![1, 4], vec![5]]);
+    let leaders: Vec<usize> = clusters.iter().map(|c| c.representative).collect();
+    assert_eq!(leaders, vec![0, 1, 5]);
+}
+
+#[test]
+fn test_clusters_skip_removed_strings() {
+    let mut db = vendor_db();
+    db.remove(0);
+
+    let clusters = connected_components(&db, Cosine, 0.7).unwrap();
+    assert!(clusters.iter().all(|c| !c.members.contains(&0)));
+    assert_eq!(clusters.iter().map(|c| c.members.len()).sum::<usize>(), 5);
+}
+
+#[test]
+fn test_clusters_invalid_threshold() {
+    let db = vendor_db();
+    assert_eq!(
+        leader_clusters(&db, Cosine, 1.5).unwrap_err(),
+        SearchError::InvalidThreshold(1.5)
+    );
+}
@@ -0,0 +1,91 @@
+use rayon::prelude::*;
+use simstring_rust::{CharacterNgrams, ConcurrentHashDb, Cosine, Dice, HashDb, Searcher};
+use std::sync::Arc;
+
+const NAMES: [&str; 8] = ["foo", "fooo", "food", "bar", "bars", "baz", "qux", "foo"];
+
+#[test]
+fn test_concurrent_db_matches_hashdb() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let concurrent = ConcurrentHashDb::with_shards(feature_extractor.clone(), 3);
+    let mut db = HashDb::new(feature_extractor);
+    let mut ids = Vec::new();
+    for name in NAMES {
+        ids.push(concurrent.insert(name.to_string()));
+        db.insert(name.to_string());
+    }
+
+    assert_eq!(concurrent.total_strings(), NAMES.len());
+    for (id, name) in ids.iter().zip(NAMES) {
+        assert_eq!(concurrent.get_string(*id).as_deref(), Some(name));
+    }
+
+    let searcher = Searcher::new(&db, Dice);
+    for query in ["foo", "bar", "ba", "nothing"] {
+        assert_eq!(
+            concurrent.search(Dice, query, 0.5).unwrap(),
+            searcher.search(query, 0.5).unwrap()
+        );
+        let ranked: Vec<(String, f64)> = searcher
+            .ranked_search(query, 0.5)
+            .unwrap()
+            .into_iter()
+            .map(|(text, score)| (text.to_string(), score))
+            .collect();
+        assert_eq!(concurrent.ranked_search(Dice, query, 0.5).unwrap(), ranked);
+    }
+
+    // Duplicates keep distinct ids
+    let exact = concurrent.ranked_search_ids(Cosine, "foo", 1.0).unwrap();
+    assert_eq!(exact, vec![(ids[0], 1.0), (ids[7], 1.0)]);
+}
+
+#[test]
+fn test_concurrent_inserts_during_searches() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let db = ConcurrentHashDb::with_shards(feature_extractor, 4);
+    db.insert("seed".to_string());
+
+    std::thread::scope(|scope| {
+        for writer in 0..4 {
+            let db = &db;
+            scope.spawn(move || {
+                for i in 0..50 {
+                    let text = format!("term {writer} {i}");
+                    let id = db.insert(text.clone());
+                    // An insert is visible to every search starting after it returned
+                    assert!(db
+                        .ranked_search_ids(Cosine, &text, 1.0)
+                        .unwrap()
+                        .iter()
+                        .any(|&(found, _)| found == id));
+                }
+            });
+        }
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..50 {
+                    assert!(db
+                        .search(Cosine, "seed", 1.0)
+                        .unwrap()
+                        .contains(&"seed".to_string()));
+                }
+            });
+        }
+        // Searches and inserts issued from inside the rayon pool, so a worker waiting on rayon
+        // work could pick up an insert into a shard it is searching
+        scope.spawn(|| {
+            (0..200).into_par_iter().for_each(|i| {
+                if i % 4 == 0 {
+                    db.insert(format!("pool {i}"));
+                }
+                assert!(db
+                    .search(Cosine, "seed", 1.0)
+                    .unwrap()
+                    .contains(&"seed".to_string()));
+            });
+        });
+    });
+
+    assert_eq!(db.total_strings(), 251);
+}
@@ -1,4 +1,11 @@
-use simstring_rust::{CharacterNgrams, Database, HashDb};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use simstring_rust::database::{MalformedLine, MalformedReason, TextOptions, TextReport};
+use simstring_rust::{
+    CharacterNgrams, Cosine, Database, DatabaseMut, FeatureExtractor, HashDb, Normalizer,
+    PersistError, Searcher, WordNgrams,
+};
+use std::io::Write;
 use std::sync::Arc;
 
 #[test]
@@ -142,6 +149,28 @@ fn test_db_clear() {
     assert_eq!(db.interner().lock().unwrap().len(), 0);
 }
 
+fn fill<D: DatabaseMut>(db: &mut D, texts: &[&str]) {
+    db.clear();
+    for text in texts {
+        db.insert(text.to_string());
+    }
+}
+
+#[test]
+fn test_database_mut_is_generic_over_databases() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("stale".to_string());
+
+    fill(&mut db, &["apple", "apply"]);
+    assert_eq!(db.total_strings(), 2);
+    assert_eq!(db.get_string(0), Some("apple"));
+    assert!(!DatabaseMut::interner(&db).lock().unwrap().is_empty());
+
+    let searcher = Searcher::new(&db, Cosine);
+    assert_eq!(searcher.search("apple", 1.0).unwrap(), vec!["apple"]);
+}
+
 #[test]
 fn test_total_strings() {
     let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
@@ -151,3 +180,458 @@ fn test_total_strings() {
 
     assert_eq!(db.total_strings(), 2);
 }
+
+#[test]
+fn test_save_and_load_roundtrip() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor.clone());
+    for s in ["foo", "bar", "fooo", "barbar", ""] {
+        db.insert(s.to_string());
+    }
+
+    let path = std::env::temp_dir().join(format!("simstring_rust_{}.db", std::process::id()));
+    db.save(&path).unwrap();
+    let loaded = HashDb::load(&path, feature_extractor).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.total_strings(), db.total_strings());
+    assert_eq!(loaded.max_feature_len(), db.max_feature_len());
+    for id in 0..db.total_strings() {
+        assert_eq!(loaded.get_string(id), db.get_string(id));
+        assert_eq!(loaded.get_features(id), db.get_features(id));
+        let size = db.get_features(id).unwrap().len();
+        for &feature in db.get_features(id).unwrap() {
+            assert_eq!(
+                loaded.lookup_strings(size, feature),
+                db.lookup_strings(size, feature)
+            );
+        }
+    }
+
+    let original = db.interner();
+    let restored = loaded.interner();
+    let original = original.lock().unwrap();
+    let restored = restored.lock().unwrap();
+    assert_eq!(restored.len(), original.len());
+    for (spur, feature) in original.iter() {
+        assert_eq!(restored.resolve(&spur), feature);
+    }
+}
+
+#[test]
+fn test_loaded_db_accepts_new_inserts() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor.clone());
+    db.insert("hello".to_string());
+
+    let mut buf = Vec::new();
+    db.write_to(&mut buf).unwrap();
+    let mut loaded = HashDb::read_from(&mut buf.as_slice(), feature_extractor).unwrap();
+    loaded.insert("help".to_string());
+    db.insert("help".to_string());
+
+    assert_eq!(loaded.get_string(1), Some("help"));
+    assert_eq!(loaded.get_features(1), db.get_features(1));
+}
+
+#[test]
+fn test_load_rejects_mismatched_extractor() {
+    let mut db = HashDb::new(Arc::new(CharacterNgrams::new(2, "$")));
+    db.insert("hello".to_string());
+
+    let mut buf = Vec::new();
+    db.write_to(&mut buf).unwrap();
+
+    let err =
+        HashDb::read_from(&mut buf.as_slice(), Arc::new(CharacterNgrams::new(3, "$"))).unwrap_err();
+    assert!(matches!(err, PersistError::ExtractorMismatch { .. }));
+
+    let err = HashDb::read_from(&mut buf.as_slice(), Arc::new(WordNgrams::default())).unwrap_err();
+    assert!(matches!(err, PersistError::ExtractorMismatch { .. }));
+}
+
+#[test]
+fn test_normalizer_is_recorded_in_saved_db() {
+    let feature_extractor = Arc::new(Normalizer::standard(CharacterNgrams::new(2, "$")));
+    let mut db = HashDb::new(feature_extractor.clone());
+    db.insert("Café".to_string());
+
+    let mut buf = Vec::new();
+    db.write_to(&mut buf).unwrap();
+
+    let loaded = HashDb::read_from(&mut buf.as_slice(), feature_extractor).unwrap();
+    let searcher = Searcher::new(&loaded, Cosine);
+    assert_eq!(searcher.search("CAFE", 1.0).unwrap(), vec!["Café"]);
+
+    let err =
+        HashDb::read_from(&mut buf.as_slice(), Arc::new(CharacterNgrams::new(2, "$"))).unwrap_err();
+    assert!(matches!(err, PersistError::ExtractorMismatch { .. }));
+}
+
+#[test]
+fn test_load_rejects_invalid_data() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let err =
+        HashDb::read_from(&mut &b"not a database"[..], feature_extractor.clone()).unwrap_err();
+    assert!(matches!(err, PersistError::InvalidMagic));
+
+    let mut db = HashDb::new(feature_extractor.clone());
+    db.insert("hello".to_string());
+    let mut buf = Vec::new();
+    db.write_to(&mut buf).unwrap();
+    buf.truncate(buf.len() - 3);
+    let err = HashDb::read_from(&mut buf.as_slice(), feature_extractor).unwrap_err();
+    assert!(matches!(err, PersistError::Io(_)));
+}
+
+#[test]
+fn test_load_rejects_corrupt_lengths() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let db = HashDb::new(feature_extractor.clone());
+    let mut buf = Vec::new();
+    db.write_to(&mut buf).unwrap();
+
+    // Magic, version and descriptor, then the (empty) interner, then the number of strings
+    let offset = 8 + 4 + 8 + feature_extractor.descriptor().len() + 8;
+    for len in [u64::MAX >> 1, 1 << 40] {
+        buf[offset..offset + 8].copy_from_slice(&len.to_le_bytes());
+        let err = HashDb::read_from(&mut buf.as_slice(), feature_extractor.clone()).unwrap_err();
+        assert!(matches!(err, PersistError::Io(_)), "{err}");
+    }
+}
+
+#[test]
+fn test_remove_unlinks_string_from_index() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("cat".to_string());
+    db.insert("bat".to_string());
+    db.insert("horse".to_string());
+
+    let size = db.get_features(0).unwrap().len();
+    let at1_spur = db.interner().lock().unwrap().get("at1").unwrap();
+    assert!(db.remove(0));
+    assert!(!db.remove(0));
+    assert!(!db.remove(42));
+
+    assert!(db.is_removed(0));
+    assert_eq!(db.get_string(0), None);
+    assert_eq!(db.get_features(0), None);
+    assert_eq!(db.total_strings(), 2);
+    let ids = db.lookup_strings(size, at1_spur).unwrap();
+    assert!(!ids.contains(&0));
+    assert!(ids.contains(&1));
+
+    // Other ids are unaffected
+    assert_eq!(db.get_string(1), Some("bat"));
+    assert_eq!(db.get_string(2), Some("horse"));
+
+    // Removing the only string of a size drops the bucket entirely
+    assert!(db.remove(2));
+    assert_eq!(db.max_feature_len(), size);
+}
+
+#[test]
+fn test_remove_str_removes_all_duplicates() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("foo".to_string());
+    db.insert("bar".to_string());
+    db.insert("foo".to_string());
+
+    assert_eq!(db.remove_str("foo"), 2);
+    assert_eq!(db.remove_str("foo"), 0);
+    assert_eq!(
+        db.iter().collect::<Vec<_>>(),
+        vec![(1, "bar")],
+        "only the untouched string should remain"
+    );
+}
+
+#[test]
+fn test_update_keeps_id() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("cat".to_string());
+    db.insert("dog".to_string());
+
+    let old_size = db.get_features(0).unwrap().len();
+    let ca1_spur = db.interner().lock().unwrap().get("ca1").unwrap();
+    assert!(db.update(0, "tiger".to_string()));
+
+    assert_eq!(db.get_string(0), Some("tiger"));
+    assert!(db
+        .lookup_strings(old_size, ca1_spur)
+        .is_none_or(|ids| !ids.contains(&0)));
+
+    let new_features = db.get_features(0).unwrap().clone();
+    for feature in &new_features {
+        assert!(db
+            .lookup_strings(new_features.len(), *feature)
+            .unwrap()
+            .contains(&0));
+    }
+
+    db.remove(1);
+    assert!(!db.update(1, "wolf".to_string()));
+    assert!(!db.update(5, "wolf".to_string()));
+}
+
+#[test]
+fn test_compact_renumbers_ids() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in ["a", "bb", "cc", "ddd"] {
+        db.insert(s.to_string());
+    }
+    db.remove(0);
+    db.remove(2);
+
+    let remap = db.compact();
+    assert_eq!(remap, vec![None, Some(0), None, Some(1)]);
+    assert_eq!(db.total_strings(), 2);
+    assert_eq!(db.get_string(0), Some("bb"));
+    assert_eq!(db.get_string(1), Some("ddd"));
+    assert_eq!(db.get_string(2), None);
+
+    for id in 0..2 {
+        let features = db.get_features(id).unwrap();
+        for feature in features {
+            let ids = db.lookup_strings(features.len(), *feature).unwrap();
+            assert!(ids.contains(&id));
+            assert!(ids.iter().all(|&other| other < 2));
+        }
+    }
+}
+
+#[test]
+fn test_save_and_load_preserves_removed_ids() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor.clone());
+    db.insert("foo".to_string());
+    db.insert("bar".to_string());
+    db.remove(0);
+
+    let mut buf = Vec::new();
+    db.write_to(&mut buf).unwrap();
+    let loaded = HashDb::read_from(&mut buf.as_slice(), feature_extractor).unwrap();
+
+    assert!(loaded.is_removed(0));
+    assert_eq!(loaded.get_string(0), None);
+    assert_eq!(loaded.get_string(1), Some("bar"));
+    assert_eq!(loaded.total_strings(), 1);
+}
+
+#[test]
+fn test_payloads_follow_their_strings() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db: HashDb<String> = HashDb::with_payloads(feature_extractor.clone());
+    db.insert_with_payload("aspirin".to_string(), "C0004057".to_string());
+    db.insert_with_payload("ibuprofen".to_string(), "C0020740".to_string());
+    db.insert("paracetamol".to_string());
+
+    assert_eq!(db.payload(0).map(String::as_str), Some("C0004057"));
+    assert_eq!(db.payload(2).map(String::as_str), Some(""));
+    *db.payload_mut(2).unwrap() = "C0000970".to_string();
+
+    db.remove(0);
+    assert_eq!(db.payload(0), None);
+    db.compact();
+    assert_eq!(db.payload(0).map(String::as_str), Some("C0020740"));
+    assert_eq!(db.payload(1).map(String::as_str), Some("C0000970"));
+
+    let mut buf = Vec::new();
+    db.write_to(&mut buf).unwrap();
+    let loaded: HashDb<String> =
+        HashDb::read_with_payloads(&mut buf.as_slice(), feature_extractor.clone()).unwrap();
+    assert_eq!(loaded.payload(1).map(String::as_str), Some("C0000970"));
+    assert_eq!(loaded.get_string(1), Some("paracetamol"));
+
+    // A database without payloads cannot supply real payload values
+    let mut plain = HashDb::new(feature_extractor.clone());
+    plain.insert("aspirin".to_string());
+    let mut buf = Vec::new();
+    plain.write_to(&mut buf).unwrap();
+    let err =
+        HashDb::<String>::read_with_payloads(&mut buf.as_slice(), feature_extractor).unwrap_err();
+    assert!(matches!(err, PersistError::Payload(_)));
+}
+
+#[test]
+fn test_freeze_and_thaw_preserve_entries() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db: HashDb<u32> = HashDb::with_payloads(feature_extractor);
+    db.insert_with_payload("hello".to_string(), 1);
+    db.insert_with_payload("help".to_string(), 2);
+    db.insert_with_payload("world".to_string(), 3);
+    db.remove(1);
+    let features: Vec<_> = (0..3).map(|id| db.get_features(id).cloned()).collect();
+
+    let frozen = db.freeze();
+    assert_eq!(frozen.total_strings(), 2);
+    assert_eq!(frozen.get_string(0), Some("hello"));
+    assert_eq!(frozen.get_string(1), None);
+    assert_eq!(frozen.payload(2), Some(&3));
+    assert_eq!(
+        frozen.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+        vec![0, 2]
+    );
+
+    let mut thawed = frozen.thaw();
+    for (id, expected) in features.iter().enumerate() {
+        assert_eq!(thawed.get_features(id).cloned(), *expected);
+    }
+    // Re-inserting a known string reuses the existing feature ids
+    thawed.insert_with_payload("hello".to_string(), 4);
+    assert_eq!(thawed.get_features(3), features[0].as_ref());
+    assert_eq!(thawed.payload(3), Some(&4));
+}
+
+#[test]
+fn test_freeze_with_shared_interner() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("hello".to_string());
+    let interner = db.interner();
+
+    let frozen = db.freeze();
+    assert_eq!(
+        frozen.query_features("hello"),
+        *frozen.get_features(0).unwrap()
+    );
+    assert_eq!(interner.lock().unwrap().len(), 6);
+}
+
+fn assert_same_db<P>(parallel: &HashDb<P>, sequential: &HashDb<P>)
+where
+    P: Send + Sync + 'static,
+{
+    assert_eq!(parallel.strings, sequential.strings);
+    for id in 0..sequential.string_id_bound() {
+        assert_eq!(parallel.get_features(id), sequential.get_features(id));
+    }
+    let parallel_interner = parallel.interner();
+    let sequential_interner = sequential.interner();
+    let parallel_interner = parallel_interner.lock().unwrap();
+    let sequential_interner = sequential_interner.lock().unwrap();
+    assert!(parallel_interner.iter().eq(sequential_interner.iter()));
+    assert_eq!(parallel.max_feature_len(), sequential.max_feature_len());
+}
+
+#[test]
+fn test_from_iter_parallel_matches_sequential_inserts() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(3, "$"));
+    let texts: Vec<String> = (0..2000)
+        .map(|i| format!("entry {} {}", i % 37, i * 7919 % 1013))
+        .collect();
+
+    let mut sequential = HashDb::new(feature_extractor.clone());
+    for text in &texts {
+        sequential.insert(text.clone());
+    }
+    let parallel = HashDb::from_iter_parallel(feature_extractor, texts);
+    assert_same_db(&parallel, &sequential);
+}
+
+#[test]
+fn test_extend_parallel_appends_to_existing_db() {
+    let feature_extractor = Arc::new(WordNgrams::new(2, " ", " "));
+    let first = ["new york city", "new jersey", "york"];
+    let second = ["jersey city", "new york", "", "new new new"];
+
+    let mut sequential = HashDb::new(feature_extractor.clone());
+    for text in first.iter().chain(&second) {
+        sequential.insert(text.to_string());
+    }
+
+    let mut parallel = HashDb::new(feature_extractor);
+    for text in first {
+        parallel.insert(text.to_string());
+    }
+    parallel.extend_parallel(second.iter().map(|text| text.to_string()));
+    parallel.extend_parallel(Vec::new());
+    assert_same_db(&parallel, &sequential);
+}
+
+#[test]
+fn test_from_iter_parallel_with_payloads() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let entries = [("apple", 3u64), ("apply", 5), ("maple", 8)];
+
+    let mut sequential = HashDb::with_payloads(feature_extractor.clone());
+    for (text, count) in entries {
+        sequential.insert_with_payload(text.to_string(), count);
+    }
+    let parallel = HashDb::from_iter_parallel_with_payloads(
+        feature_extractor,
+        entries.map(|(text, count)| (text.to_string(), count)),
+    );
+    assert_same_db(&parallel, &sequential);
+    assert_eq!(parallel.payload(1), Some(&5));
+    assert_eq!(parallel.payload(2), Some(&8));
+}
+
+#[test]
+fn test_from_reader_plain_lines() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let input = "hello\r\n  help \n\nhello\nworld";
+    let (db, report) =
+        HashDb::from_reader(input.as_bytes(), feature_extractor, &TextOptions::default()).unwrap();
+
+    assert_eq!(db.strings, vec!["hello", "  help ", "", "hello", "world"]);
+    assert_eq!(report.inserted, 5);
+    assert!(report.malformed.is_empty());
+}
+
+#[test]
+fn test_from_reader_options_and_malformed_lines() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut input = b"hello\n  help \n   \n".to_vec();
+    input.extend_from_slice(b"bad \xff line\nhello\nhelp\n");
+    let options = TextOptions {
+        trim: true,
+        skip_blank: true,
+        dedup: true,
+    };
+    let (db, report) = HashDb::from_reader(input.as_slice(), feature_extractor, &options).unwrap();
+
+    assert_eq!(db.strings, vec!["hello", "help"]);
+    assert_eq!(
+        report,
+        TextReport {
+            inserted: 2,
+            skipped_blank: 1,
+            skipped_duplicates: 2,
+            malformed: vec![MalformedLine {
+                line_number: 4,
+                reason: MalformedReason::InvalidUtf8,
+            }],
+        }
+    );
+}
+
+#[test]
+fn test_from_path_gzip_tsv() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let path = std::env::temp_dir().join(format!("simstring_rust_{}.tsv.gz", std::process::id()));
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(b"aspirin\tC0004057\nno payload\nibuprofen\tC0020740\textra\n")
+        .unwrap();
+    std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+    let result = HashDb::from_tsv_path(&path, feature_extractor, &TextOptions::default());
+    std::fs::remove_file(&path).unwrap();
+    let (db, report) = result.unwrap();
+
+    assert_eq!(db.strings, vec!["aspirin", "ibuprofen"]);
+    assert_eq!(db.payload(0).map(String::as_str), Some("C0004057"));
+    assert_eq!(db.payload(1).map(String::as_str), Some("C0020740\textra"));
+    assert_eq!(
+        report.malformed,
+        vec![MalformedLine {
+            line_number: 2,
+            reason: MalformedReason::MissingPayload,
+        }]
+    );
+}
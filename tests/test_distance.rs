@@ -0,0 +1,48 @@
+use simstring_rust::distance::{
+    damerau_levenshtein, damerau_levenshtein_similarity, jaro, jaro_winkler, levenshtein,
+    EditMeasure,
+};
+
+fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-3
+}
+
+#[test]
+fn test_damerau_levenshtein() {
+    assert_eq!(levenshtein("recieve", "receive"), 2);
+    assert_eq!(damerau_levenshtein("recieve", "receive"), 1);
+    assert_eq!(damerau_levenshtein("kitten", "sitting"), 3);
+    assert_eq!(damerau_levenshtein("", "abc"), 3);
+    assert_eq!(damerau_levenshtein("abc", ""), 3);
+    // Unrestricted: "ca" -> "ac" -> "abc"
+    assert_eq!(damerau_levenshtein("ca", "abc"), 2);
+    assert_eq!(damerau_levenshtein("ab", "ba"), 1);
+    assert_eq!(damerau_levenshtein("日本語", "本日語"), 1);
+    assert_eq!(damerau_levenshtein_similarity("abcd", "abdc"), 0.75);
+    assert_eq!(damerau_levenshtein_similarity("", ""), 1.0);
+}
+
+#[test]
+fn test_jaro_winkler() {
+    assert!(approx_eq(jaro("martha", "marhta"), 0.944));
+    assert!(approx_eq(jaro_winkler("martha", "marhta"), 0.961));
+    assert!(approx_eq(jaro("dixon", "dicksonx"), 0.767));
+    assert!(approx_eq(jaro_winkler("dixon", "dicksonx"), 0.813));
+    assert_eq!(jaro("abc", "xyz"), 0.0);
+    assert_eq!(jaro("", ""), 1.0);
+    assert_eq!(jaro("", "abc"), 0.0);
+    assert_eq!(jaro_winkler("same", "same"), 1.0);
+}
+
+#[test]
+fn test_edit_measure_similarity() {
+    assert_eq!(EditMeasure::Levenshtein.similarity("abcd", "abdc"), 0.5);
+    assert_eq!(
+        EditMeasure::DamerauLevenshtein.similarity("abcd", "abdc"),
+        0.75
+    );
+    assert_eq!(
+        EditMeasure::JaroWinkler.similarity("martha", "marhta"),
+        jaro_winkler("martha", "marhta")
+    );
+}
@@ -1,5 +1,9 @@
 use lasso::Rodeo;
-use simstring_rust::{CharacterNgrams, FeatureExtractor, WordNgrams};
+use simstring_rust::{
+    CharacterNgrams, CompositeExtractor, Cosine, Dice, FeatureExtractor, GraphemeNgrams, HashDb,
+    Normalization, Normalizer, PositionalQgrams, Searcher, SkipGrams, WordNgrams,
+};
+use std::sync::Arc;
 
 #[test]
 fn test_character_ngrams_basic() {
@@ -186,6 +190,26 @@ mod word_ngrams_tests {
         assert_eq!(resolved_spaces, vec!["$ $1"]);
     }
 
+    #[test]
+    fn test_word_ngram_from_descriptor() {
+        for extractor in [
+            WordNgrams::default(),
+            WordNgrams::new(3, "\t", "<PAD>"),
+            WordNgrams::new(2, "\"\\", "\u{7f}\n😄"),
+        ] {
+            let rebuilt = WordNgrams::from_descriptor(&extractor.descriptor()).unwrap();
+            assert_eq!(rebuilt.descriptor(), extractor.descriptor());
+        }
+
+        assert!(WordNgrams::from_descriptor("CharacterNgrams(n=2, endmarker=\"$\")").is_none());
+        assert!(CharacterNgrams::from_descriptor(&WordNgrams::default().descriptor()).is_none());
+        for extractor in [CharacterNgrams::default(), CharacterNgrams::new(4, "\"#\"")] {
+            let rebuilt = CharacterNgrams::from_descriptor(&extractor.descriptor()).unwrap();
+            assert_eq!(rebuilt.descriptor(), extractor.descriptor());
+        }
+        assert!(WordNgrams::from_descriptor("WordNgrams(n=2, splitter=\" \")").is_none());
+    }
+
     #[test]
     fn test_word_ngram_parameterized_cases() {
         let mut interner = Rodeo::default();
@@ -256,3 +280,300 @@ fn test_word_ngrams_n_zero() {
     let features = extractor.features("hello world", &mut interner);
     assert!(features.is_empty(), "Features should be empty when n=0");
 }
+
+#[test]
+fn test_normalizer_steps() {
+    let lower = Normalizer::new(CharacterNgrams::default(), vec![Normalization::Lowercase]);
+    assert_eq!(lower.normalize("CAFÉ"), "café");
+    // Lowercasing leaves "ß" alone, case folding expands it
+    assert_ne!(lower.normalize("STRASSE"), lower.normalize("straße"));
+    let fold = Normalizer::new(CharacterNgrams::default(), vec![Normalization::CaseFold]);
+    assert_eq!(fold.normalize("STRASSE"), "strasse");
+    assert_eq!(fold.normalize("straße"), "strasse");
+    assert_eq!(fold.normalize("ΟΔΟΣ"), fold.normalize("οδος"));
+
+    let strip = Normalizer::new(
+        CharacterNgrams::default(),
+        vec![Normalization::StripDiacritics],
+    );
+    // Precomposed and decomposed accents are both removed
+    assert_eq!(strip.normalize("Caf\u{e9}"), "Cafe");
+    assert_eq!(strip.normalize("Cafe\u{301}"), "Cafe");
+
+    let nfc = Normalizer::new(CharacterNgrams::default(), vec![Normalization::Nfc]);
+    assert_eq!(nfc.normalize("cafe\u{301}"), "caf\u{e9}");
+    let nfkc = Normalizer::new(CharacterNgrams::default(), vec![Normalization::Nfkc]);
+    assert_eq!(nfkc.normalize("ﬁle ＡＢＣ"), "file ABC");
+
+    let cleanup = Normalizer::new(
+        CharacterNgrams::default(),
+        vec![
+            Normalization::RemovePunctuation,
+            Normalization::CollapseWhitespace,
+        ],
+    );
+    assert_eq!(cleanup.normalize("  Acme,  Inc. (US)\t"), "Acme Inc US");
+}
+
+#[test]
+fn test_normalizer_unifies_features() {
+    let mut interner = Rodeo::default();
+    let extractor = Normalizer::standard(CharacterNgrams::new(2, "$"));
+    let expected = CharacterNgrams::new(2, "$").features("cafe", &mut interner);
+
+    for text in ["Café", "CAFE", "cafe\u{301}", "  café "] {
+        assert_eq!(
+            extractor.features(text, &mut interner),
+            expected,
+            "{text:?}"
+        );
+    }
+    assert_eq!(
+        extractor.query_features("CAFÉ", &|feature| interner.get(feature)),
+        expected
+    );
+}
+
+#[test]
+fn test_normalizer_wraps_runtime_extractor() {
+    let mut interner = Rodeo::default();
+    let inner: Arc<dyn FeatureExtractor> = Arc::new(CharacterNgrams::new(2, "$"));
+    let extractor = Normalizer::standard(Arc::clone(&inner));
+
+    assert_eq!(
+        extractor.features("STRASSE", &mut interner),
+        inner.features("strasse", &mut interner)
+    );
+    assert_eq!(
+        extractor.descriptor(),
+        Normalizer::standard(CharacterNgrams::new(2, "$")).descriptor()
+    );
+
+    // The wrapped extractor can itself be shared as a runtime extractor
+    let feature_extractor: Arc<dyn FeatureExtractor> = Arc::new(extractor);
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("Straße".to_string());
+    let searcher = Searcher::new(&db, Cosine);
+    assert_eq!(searcher.search("STRASSE", 1.0).unwrap(), vec!["Straße"]);
+}
+
+#[test]
+fn test_normalizer_descriptor() {
+    let extractor = Normalizer::new(
+        WordNgrams::default(),
+        vec![Normalization::Lowercase, Normalization::Nfc],
+    );
+    assert_eq!(
+        extractor.descriptor(),
+        format!(
+            "Normalizer(steps=[Lowercase, Nfc], {})",
+            WordNgrams::default().descriptor()
+        )
+    );
+    assert_ne!(
+        extractor.descriptor(),
+        Normalizer::new(WordNgrams::default(), vec![Normalization::Lowercase]).descriptor()
+    );
+}
+
+fn resolve(interner: &Rodeo, features: &[lasso::Spur]) -> Vec<String> {
+    features
+        .iter()
+        .map(|s| interner.resolve(s).to_string())
+        .collect()
+}
+
+#[test]
+fn test_grapheme_ngrams_combining_marks() {
+    let mut interner = Rodeo::default();
+    let extractor = GraphemeNgrams::new(2, "$");
+
+    // Decomposed "é" stays together with its accent
+    let features = extractor.features("e\u{301}a", &mut interner);
+    assert_eq!(
+        resolve(&interner, &features),
+        vec!["$e\u{301}1", "e\u{301}a1", "a$1"]
+    );
+
+    // "नमस्ते" is 6 chars but 3 grapheme clusters, the conjunct "स्ते" being one
+    let features = extractor.features("नमस्ते", &mut interner);
+    assert_eq!(
+        resolve(&interner, &features),
+        vec!["$न1", "नम1", "मस्ते1", "स्ते$1"]
+    );
+    assert_eq!(
+        CharacterNgrams::new(2, "$")
+            .features("नमस्ते", &mut interner)
+            .len(),
+        7
+    );
+}
+
+#[test]
+fn test_grapheme_ngrams_zwj_and_flags() {
+    let mut interner = Rodeo::default();
+    let extractor = GraphemeNgrams::new(2, "$");
+    let family = "👨\u{200d}👩\u{200d}👧";
+    let thumbs = "👍🏽";
+    let flag = "🇬🇭";
+
+    let text = format!("{family}{thumbs}{flag}");
+    let features = extractor.features(&text, &mut interner);
+    assert_eq!(
+        resolve(&interner, &features),
+        vec![
+            format!("${family}1"),
+            format!("{family}{thumbs}1"),
+            format!("{thumbs}{flag}1"),
+            format!("{flag}$1"),
+        ]
+    );
+}
+
+#[test]
+fn test_grapheme_ngrams_matches_characters_for_plain_text() {
+    let mut interner = Rodeo::default();
+    let graphemes = GraphemeNgrams::new(3, "$").features("abab", &mut interner);
+    let chars = CharacterNgrams::new(3, "$").features("abab", &mut interner);
+    assert_eq!(graphemes, chars);
+    assert!(GraphemeNgrams::new(0, "$")
+        .features("abc", &mut interner)
+        .is_empty());
+    assert_ne!(
+        GraphemeNgrams::new(3, "$").descriptor(),
+        CharacterNgrams::new(3, "$").descriptor()
+    );
+}
+
+#[test]
+fn test_skip_grams() {
+    let mut interner = Rodeo::default();
+    let features = SkipGrams::new(2, 1, "$").features("abc", &mut interner);
+    assert_eq!(
+        resolve(&interner, &features),
+        vec!["$a1", "$b1", "ab1", "ac1", "bc1", "b$1", "c$1"]
+    );
+
+    let contiguous = SkipGrams::new(2, 0, "$").features("abab", &mut interner);
+    let ngrams = CharacterNgrams::new(2, "$").features("abab", &mut interner);
+    assert_eq!(contiguous, ngrams);
+
+    // Without padding, only grams of the text itself remain
+    let features = SkipGrams::new(3, 1, "").features("abcd", &mut interner);
+    assert_eq!(
+        resolve(&interner, &features),
+        vec!["abc1", "abd1", "acd1", "bcd1"]
+    );
+}
+
+#[test]
+fn test_skip_grams_tolerate_transpositions() {
+    let sku_db = |extractor: Arc<dyn FeatureExtractor>| {
+        let mut db = HashDb::new(extractor);
+        db.insert("XR-4471-B".to_string());
+        db
+    };
+    let query = "XR-4741-B";
+
+    let bigram_db = sku_db(Arc::new(CharacterNgrams::new(2, "$")));
+    let skip_db = sku_db(Arc::new(SkipGrams::new(2, 2, "$")));
+    let score = |db: &HashDb| {
+        let searcher = Searcher::new(db, Dice);
+        searcher.ranked_search(query, 0.1).unwrap()[0].1
+    };
+    let bigram_score = score(&bigram_db);
+    let skip_score = score(&skip_db);
+    assert!(skip_score > bigram_score, "{skip_score} <= {bigram_score}");
+}
+
+#[test]
+fn test_positional_qgrams() {
+    let mut interner = Rodeo::default();
+    let extractor = PositionalQgrams::new(2, 2, "$");
+    let features = extractor.features("abcd", &mut interner);
+    assert_eq!(
+        resolve(&interner, &features),
+        vec!["$a@0:1", "ab@0:1", "bc@0:1", "cd@1:1", "d$@1:1"]
+    );
+
+    // The same bigram in different halves gives different features
+    let features = extractor.features("abab", &mut interner);
+    assert_eq!(
+        resolve(&interner, &features),
+        vec!["$a@0:1", "ab@0:1", "ba@0:1", "ab@1:1", "b$@1:1"]
+    );
+
+    assert_eq!(
+        PositionalQgrams::new(2, 0, "$").descriptor(),
+        PositionalQgrams::new(2, 1, "$").descriptor()
+    );
+    let mut db = HashDb::new(Arc::new(PositionalQgrams::default()));
+    db.insert("AB12CD".to_string());
+    db.insert("CD12AB".to_string());
+    let searcher = Searcher::new(&db, Cosine);
+    assert_eq!(searcher.search("AB12CD", 0.9).unwrap(), vec!["AB12CD"]);
+}
+
+#[test]
+fn test_composite_extractor_namespaces_children() {
+    let mut interner = Rodeo::default();
+    let extractor = CompositeExtractor::new(vec![
+        Arc::new(CharacterNgrams::new(2, "$")),
+        Arc::new(CharacterNgrams::new(2, "#")),
+    ]);
+    let features = extractor.features("ab", &mut interner);
+    let mut resolved = resolve(&interner, &features);
+    resolved.sort();
+    assert_eq!(
+        resolved,
+        vec!["0|$a1", "0|ab1", "0|b$1", "1|#a1", "1|ab1", "1|b#1"]
+    );
+    // Both children produce "ab1", but the namespaced features stay distinct
+    assert!(interner.get("ab1").is_none());
+}
+
+#[test]
+fn test_character_ngrams_range() {
+    let mut interner = Rodeo::default();
+    let extractor = CharacterNgrams::range(1..=3);
+    assert_eq!(extractor.children().len(), 3);
+
+    let combined = extractor.features("hello", &mut interner).len();
+    let separate: usize = (1..=3)
+        .map(|n| {
+            CharacterNgrams::new(n, "$")
+                .features("hello", &mut interner)
+                .len()
+        })
+        .sum();
+    assert_eq!(combined, separate);
+    assert_eq!(
+        extractor.descriptor(),
+        format!(
+            "CompositeExtractor([{}, {}, {}])",
+            CharacterNgrams::new(1, "$").descriptor(),
+            CharacterNgrams::new(2, "$").descriptor(),
+            CharacterNgrams::new(3, "$").descriptor()
+        )
+    );
+}
+
+#[test]
+fn test_composite_extractor_search() {
+    let extractor = CompositeExtractor::new(vec![
+        Arc::new(CharacterNgrams::new(3, "$")),
+        Arc::new(WordNgrams::new(1, " ", " ")),
+    ]);
+    let mut db = HashDb::new(Arc::new(extractor));
+    for s in ["new york city", "york new city", "newark"] {
+        db.insert(s.to_string());
+    }
+    let searcher = Searcher::new(&db, Cosine);
+
+    let results = searcher.ranked_search("new york city", 0.5).unwrap();
+    assert_eq!(results[0], ("new york city", 1.0));
+    // Shares every word but few trigrams
+    assert_eq!(results[1].0, "york new city");
+    assert!(results[1].1 < 1.0);
+    assert!(searcher.ranked_search("unrelated", 0.5).unwrap().is_empty());
+}
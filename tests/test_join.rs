@@ -0,0 +1,165 @@
+use simstring_rust::database::StringId;
+use simstring_rust::join::{self_join, self_join_with, similarity_join};
+use simstring_rust::{
+    CharacterNgrams, Cosine, Database, Dice, HashDb, Jaccard, Measure, Overlap, SearchError,
+    Searcher,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+fn company_db() -> HashDb {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in [
+        "Acme Inc",
+        "Acme Inc.",
+        "ACME Incorporated",
+        "Globex",
+        "Globex Corp",
+        "Initech",
+        "Initech",
+        "Umbrella",
+        "",
+    ] {
+        db.insert(s.to_string());
+    }
+    db
+}
+
+fn brute_force_pairs<M: Measure>(
+    db: &dyn Database,
+    measure: &M,
+    alpha: f64,
+) -> Vec<(StringId, StringId, f64)> {
+    let mut pairs = Vec::new();
+    for a in 0..db.string_id_bound() {
+        for b in a + 1..db.string_id_bound() {
+            if let (Some(x), Some(y)) = (db.get_features(a), db.get_features(b)) {
+                let score = measure.similarity(x, y);
+                if score >= alpha {
+                    pairs.push((a, b, score));
+                }
+            }
+        }
+    }
+    pairs
+}
+
+fn assert_join_matches_brute_force<M: Measure + Copy>(measure: M) {
+    let db = company_db();
+    for alpha in [0.3, 0.5, 0.7, 0.9, 1.0] {
+        let pairs = self_join(&db, measure, alpha).unwrap();
+        assert_eq!(
+            pairs,
+            brute_force_pairs(&db, &measure, alpha),
+            "alpha {alpha}"
+        );
+    }
+}
+
+#[test]
+fn test_self_join_matches_brute_force() {
+    assert_join_matches_brute_force(Cosine);
+    assert_join_matches_brute_force(Dice);
+    assert_join_matches_brute_force(Jaccard);
+    assert_join_matches_brute_force(Overlap);
+}
+
+#[test]
+fn test_self_join_reports_each_pair_once() {
+    let db = company_db();
+    let pairs = self_join(&db, Cosine, 1.0).unwrap();
+    // Only the two identical "Initech" entries are exact duplicates
+    assert_eq!(pairs, vec![(5, 6, 1.0)]);
+}
+
+#[test]
+fn test_self_join_with_callback_skips_removed() {
+    let mut db = company_db();
+    db.remove(6);
+
+    let count = AtomicUsize::new(0);
+    self_join_with(&db, Cosine, 0.6, |a, b, score| {
+        assert!(a < b);
+        assert!(b != 6 && a != 6);
+        assert!(score >= 0.6);
+        count.fetch_add(1, Ordering::Relaxed);
+    })
+    .unwrap();
+
+    assert_eq!(
+        count.load(Ordering::Relaxed),
+        brute_force_pairs(&db, &Cosine, 0.6).len()
+    );
+}
+
+#[test]
+fn test_self_join_invalid_threshold() {
+    let db = company_db();
+    assert_eq!(
+        self_join(&db, Cosine, 0.0).unwrap_err(),
+        SearchError::InvalidThreshold(0.0)
+    );
+}
+
+fn vendor_db() -> HashDb {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in ["Acme Inc", "Globex Corporation", "Initech LLC"] {
+        db.insert(s.to_string());
+    }
+    db
+}
+
+fn probe_pairs<M: Measure + Copy>(
+    db_a: &HashDb,
+    db_b: &HashDb,
+    measure: M,
+    alpha: f64,
+) -> Vec<(StringId, StringId, f64)> {
+    let searcher = Searcher::new(db_b, measure);
+    let mut pairs = Vec::new();
+    for (a, text) in db_a.iter() {
+        for (b, score) in searcher.ranked_search_ids(text, alpha).unwrap() {
+            pairs.push((a, b, score));
+        }
+    }
+    pairs.sort_unstable_by_key(|&(a, b, _)| (a, b));
+    pairs
+}
+
+#[test]
+fn test_similarity_join_matches_search() {
+    let companies = company_db();
+    let vendors = vendor_db();
+    for alpha in [0.3, 0.5, 0.7, 1.0] {
+        assert_eq!(
+            similarity_join(&companies, &vendors, Dice, alpha).unwrap(),
+            probe_pairs(&companies, &vendors, Dice, alpha),
+            "alpha {alpha}"
+        );
+        // The smaller side is probed internally, but pairs keep the argument order
+        assert_eq!(
+            similarity_join(&vendors, &companies, Dice, alpha).unwrap(),
+            probe_pairs(&vendors, &companies, Dice, alpha),
+            "alpha {alpha}"
+        );
+    }
+}
+
+#[test]
+fn test_similarity_join_exact_matches() {
+    let pairs = similarity_join(&vendor_db(), &company_db(), Cosine, 1.0).unwrap();
+    assert_eq!(pairs, vec![(0, 0, 1.0)]);
+}
+
+#[test]
+fn test_similarity_join_incompatible_extractors() {
+    let mut other = HashDb::new(Arc::new(CharacterNgrams::new(3, "$")));
+    other.insert("Acme Inc".to_string());
+
+    assert!(matches!(
+        similarity_join(&company_db(), &other, Cosine, 0.5),
+        Err(SearchError::IncompatibleExtractors(_, _))
+    ));
+}
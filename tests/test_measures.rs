@@ -3,7 +3,7 @@ use rustc_hash::FxHashSet;
 use simstring_rust::database::{Database, HashDb, StringId};
 use simstring_rust::extractors::{CharacterNgrams, FeatureExtractor};
 use simstring_rust::measures::{Cosine, Dice, ExactMatch, Jaccard, Measure, Overlap};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
 fn approx_eq(a: f64, b: f64) -> bool {
     (a - b).abs() < 1e-9
@@ -426,8 +426,6 @@ fn test_overlap_similarity_empty_inputs() {
 
 struct MockDatabase;
 impl Database for MockDatabase {
-    fn insert(&mut self, _text: String) {}
-    fn clear(&mut self) {}
     fn lookup_strings(&self, _size: usize, _feature: Spur) -> Option<&FxHashSet<StringId>> {
         None
     }
@@ -443,7 +441,7 @@ impl Database for MockDatabase {
     fn max_feature_len(&self) -> usize {
         100
     }
-    fn interner(&self) -> Arc<Mutex<Rodeo>> {
+    fn feature_id(&self, _feature: &str) -> Option<Spur> {
         unimplemented!()
     }
     fn total_strings(&self) -> usize {
@@ -0,0 +1,120 @@
+use lasso::Rodeo;
+use simstring_rust::phonetic::{double_metaphone, metaphone, soundex};
+use simstring_rust::{
+    CharacterNgrams, CompositeExtractor, Cosine, FeatureExtractor, HashDb, PhoneticAlgorithm,
+    PhoneticExtractor, Searcher,
+};
+use std::sync::Arc;
+
+#[test]
+fn test_soundex() {
+    for (word, code) in [
+        ("Robert", "R163"),
+        ("Rupert", "R163"),
+        ("Rubin", "R150"),
+        ("Ashcraft", "A261"),
+        ("Tymczak", "T522"),
+        ("Pfister", "P236"),
+        ("Honeyman", "H555"),
+        ("catherine", "C365"),
+        ("Kathryn", "K365"),
+    ] {
+        assert_eq!(soundex(word), code, "{word}");
+    }
+    assert_eq!(soundex("42"), "");
+}
+
+#[test]
+fn test_metaphone() {
+    for (word, code) in [
+        ("Catherine", "K0RN"),
+        ("Kathryn", "K0RN"),
+        ("Knight", "NT"),
+        ("Philips", "FLPS"),
+        ("Wright", "RT"),
+        ("School", "SKL"),
+        ("Xavier", "SFR"),
+        ("Edge", "EJ"),
+    ] {
+        assert_eq!(metaphone(word), code, "{word}");
+    }
+    assert_eq!(metaphone(""), "");
+}
+
+#[test]
+fn test_double_metaphone() {
+    for (word, primary, alternate) in [
+        ("Catherine", "K0RN", "KTRN"),
+        ("Kathryn", "K0RN", "KTRN"),
+        ("Smith", "SM0", "XMT"),
+        ("Schmidt", "XMT", "SMT"),
+        ("Jose", "HS", "HS"),
+        ("Xavier", "SF", "SFR"),
+        ("Gough", "KF", "KF"),
+        ("Arnow", "ARN", "ARNF"),
+    ] {
+        assert_eq!(
+            double_metaphone(word),
+            (primary.to_string(), alternate.to_string()),
+            "{word}"
+        );
+    }
+    // Codes are at most four characters long
+    assert_eq!(double_metaphone("Maximilian").0.len(), 4);
+}
+
+#[test]
+fn test_phonetic_extractor_features() {
+    let mut interner = Rodeo::default();
+    let extractor = PhoneticExtractor::new(PhoneticAlgorithm::DoubleMetaphone);
+    let features = extractor.features("Catherine Smith", &mut interner);
+    let mut resolved: Vec<&str> = features.iter().map(|f| interner.resolve(f)).collect();
+    resolved.sort();
+    assert_eq!(resolved, vec!["K0RN1", "KTRN1", "SM01", "XMT1"]);
+
+    let extractor = PhoneticExtractor::with_ngrams(PhoneticAlgorithm::Soundex, 2, "$");
+    let features = extractor.features("Kathryn", &mut interner);
+    let resolved: Vec<&str> = features.iter().map(|f| interner.resolve(f)).collect();
+    assert_eq!(resolved, vec!["$K1", "K31", "361", "651", "5$1"]);
+
+    assert_ne!(
+        PhoneticExtractor::new(PhoneticAlgorithm::Soundex).descriptor(),
+        PhoneticExtractor::new(PhoneticAlgorithm::Metaphone).descriptor()
+    );
+}
+
+#[test]
+fn test_phonetic_search() {
+    let names = ["Kathryn", "Katrina", "Caroline"];
+    let build = |extractor: Arc<dyn FeatureExtractor>| {
+        let mut db = HashDb::new(extractor);
+        for name in names {
+            db.insert(name.to_string());
+        }
+        db
+    };
+
+    // Catherine and Kathryn only share 2 of their bigrams
+    let ngram_db = build(Arc::new(CharacterNgrams::new(2, "$")));
+    let searcher = Searcher::new(&ngram_db, Cosine);
+    assert!(!searcher
+        .search("Catherine", 0.3)
+        .unwrap()
+        .contains(&"Kathryn"));
+
+    let phonetic_db = build(Arc::new(PhoneticExtractor::default()));
+    let searcher = Searcher::new(&phonetic_db, Cosine);
+    assert_eq!(searcher.search("Catherine", 1.0).unwrap(), vec!["Kathryn"]);
+
+    // Mixed in, the shared codes lift Kathryn above the threshold
+    let composite = CompositeExtractor::new(vec![
+        Arc::new(CharacterNgrams::new(2, "$")),
+        Arc::new(PhoneticExtractor::default()),
+    ]);
+    let composite_db = build(Arc::new(composite));
+    let searcher = Searcher::new(&composite_db, Cosine);
+    assert!(searcher
+        .search("Catherine", 0.3)
+        .unwrap()
+        .contains(&"Kathryn"));
+}
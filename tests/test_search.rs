@@ -1,11 +1,13 @@
-use lasso::{Rodeo, Spur};
+use lasso::Spur;
 use rustc_hash::FxHashSet;
 use simstring_rust::database::StringId;
+use simstring_rust::distance::EditMeasure;
 use simstring_rust::{
     CharacterNgrams, Cosine, Database, Dice, ExactMatch, FeatureExtractor, HashDb, Jaccard,
-    Overlap, SearchError, Searcher,
+    Measure, Overlap, SearchError, Searcher, Verification,
 };
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 fn approx_eq(a: f64, b: f64) -> bool {
     (a - b).abs() < 1e-9
@@ -269,14 +271,6 @@ impl MockDatabase {
 }
 
 impl Database for MockDatabase {
-    fn insert(&mut self, text: String) {
-        self.real_db.insert(text);
-    }
-
-    fn clear(&mut self) {
-        self.real_db.clear();
-    }
-
     fn lookup_strings(&self, size: usize, feature: Spur) -> Option<&FxHashSet<StringId>> {
         self.real_db.lookup_strings(size, feature)
     }
@@ -299,8 +293,8 @@ impl Database for MockDatabase {
         self.real_db.max_feature_len()
     }
 
-    fn interner(&self) -> Arc<Mutex<Rodeo>> {
-        self.real_db.interner()
+    fn feature_id(&self, feature: &str) -> Option<Spur> {
+        self.real_db.feature_id(feature)
     }
 
     fn total_strings(&self) -> usize {
@@ -447,6 +441,27 @@ fn test_no_results() {
     );
 }
 
+#[test]
+fn test_search_does_not_grow_interner() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("foo".to_string());
+    db.insert("food".to_string());
+    let interned = db.interner().lock().unwrap().len();
+
+    let searcher = Searcher::new(&db, Cosine);
+    let results = searcher.ranked_search("fooz", 0.5).unwrap();
+    searcher.search("completely unseen", 0.1).unwrap();
+    searcher.top_k("zzz", 3).unwrap();
+    searcher.batch_search(&["qux", "foox"], 0.5).unwrap();
+    assert_eq!(db.interner().lock().unwrap().len(), interned);
+
+    // Unseen features never match but still count towards the query size
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].0, "foo");
+    assert!(approx_eq(results[0].1, 3.0 / 20f64.sqrt()));
+}
+
 #[test]
 fn test_search_tau_equals_one_optimization() {
     let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
@@ -495,3 +510,428 @@ fn test_search_candidates_empty_query() {
         "Search with empty query features should return empty results"
     );
 }
+
+#[test]
+fn test_search_skips_removed_and_sees_updated_strings() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("foo".to_string());
+    db.insert("fooo".to_string());
+    db.insert("bar".to_string());
+
+    db.remove(0);
+    db.update(2, "foo".to_string());
+
+    let searcher = Searcher::new(&db, Cosine);
+    let results = searcher.ranked_search("foo", 0.8).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].0, "foo");
+    assert_eq!(results[1].0, "fooo");
+}
+
+#[test]
+fn test_ranked_search_with_payloads() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db: HashDb<u32> = HashDb::with_payloads(feature_extractor);
+    db.insert_with_payload("heart attack".to_string(), 22298006);
+    db.insert_with_payload("myocardial infarction".to_string(), 22298006);
+    db.insert_with_payload("heart attacks".to_string(), 22298006);
+    db.insert_with_payload("hearth".to_string(), 1);
+
+    let searcher = Searcher::new(&db, Cosine);
+    let results = searcher
+        .ranked_search_with_payloads::<u32>("heart attack", 0.8)
+        .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].0, 0);
+    assert_eq!(results[0].1, "heart attack");
+    assert_eq!(*results[0].2, 22298006);
+    assert!(approx_eq(results[0].3, 1.0));
+    assert_eq!(results[1].0, 2);
+    assert_eq!(*results[1].2, 22298006);
+}
+
+#[test]
+fn test_ranked_search_with_payloads_distinguishes_duplicates() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db: HashDb<&'static str> = HashDb::with_payloads(feature_extractor);
+    db.insert_with_payload("cold".to_string(), "temperature");
+    db.insert_with_payload("cold".to_string(), "illness");
+
+    let searcher = Searcher::new(&db, Cosine);
+    let results = searcher
+        .ranked_search_with_payloads::<&str>("cold", 1.0)
+        .unwrap();
+
+    let payloads: Vec<(StringId, &str)> = results.iter().map(|r| (r.0, *r.2)).collect();
+    assert_eq!(payloads, vec![(0, "temperature"), (1, "illness")]);
+}
+
+#[test]
+fn test_ranked_search_with_wrong_payload_type() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db: HashDb<u32> = HashDb::with_payloads(feature_extractor);
+    db.insert_with_payload("foo".to_string(), 7);
+
+    let searcher = Searcher::new(&db, Cosine);
+    let err = searcher
+        .ranked_search_with_payloads::<String>("foo", 0.5)
+        .unwrap_err();
+    assert!(matches!(err, SearchError::PayloadTypeMismatch(_)));
+}
+
+/// Forwards everything to a `HashDb` but, like most custom databases, stores no payloads.
+struct PayloadlessDatabase {
+    real_db: HashDb,
+}
+
+impl Database for PayloadlessDatabase {
+    fn lookup_strings(&self, size: usize, feature: Spur) -> Option<&FxHashSet<StringId>> {
+        self.real_db.lookup_strings(size, feature)
+    }
+
+    fn get_string(&self, id: StringId) -> Option<&str> {
+        self.real_db.get_string(id)
+    }
+
+    fn get_features(&self, id: StringId) -> Option<&Vec<Spur>> {
+        self.real_db.get_features(id)
+    }
+
+    fn feature_extractor(&self) -> &dyn FeatureExtractor {
+        self.real_db.feature_extractor()
+    }
+
+    fn max_feature_len(&self) -> usize {
+        self.real_db.max_feature_len()
+    }
+
+    fn feature_id(&self, feature: &str) -> Option<Spur> {
+        self.real_db.feature_id(feature)
+    }
+
+    fn total_strings(&self) -> usize {
+        self.real_db.total_strings()
+    }
+}
+
+#[test]
+fn test_ranked_search_with_missing_payloads() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut real_db = HashDb::new(feature_extractor);
+    real_db.insert("foo".to_string());
+    let db = PayloadlessDatabase { real_db };
+
+    let searcher = Searcher::new(&db, Cosine);
+    assert_eq!(
+        searcher.ranked_search_with_payloads::<u32>("foo", 0.5),
+        Err(SearchError::MissingPayload(0))
+    );
+    // No matches is still an empty result, not an error
+    assert_eq!(
+        searcher.ranked_search_with_payloads::<u32>("xyz", 0.5),
+        Ok(vec![])
+    );
+}
+
+#[test]
+fn test_search_ids_distinguish_duplicates() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("foo".to_string());
+    db.insert("bar".to_string());
+    db.insert("foo".to_string());
+    db.insert("fooo".to_string());
+
+    let searcher = Searcher::new(&db, Cosine);
+    assert_eq!(searcher.search_ids("foo", 0.8).unwrap(), vec![0, 2, 3]);
+
+    let ranked = searcher.ranked_search_ids("foo", 0.8).unwrap();
+    let ids: Vec<StringId> = ranked.iter().map(|&(id, _)| id).collect();
+    assert_eq!(ids, vec![0, 2, 3]);
+    assert!(approx_eq(ranked[0].1, 1.0));
+    assert!(approx_eq(ranked[1].1, 1.0));
+    assert!(approx_eq(ranked[2].1, 0.8944271909999159));
+
+    // Scores match the string based API
+    let by_string = searcher.ranked_search("foo", 0.8).unwrap();
+    for (&(id, score), &(text, expected)) in ranked.iter().zip(&by_string) {
+        assert_eq!(db.get_string(id), Some(text));
+        assert!(approx_eq(score, expected));
+    }
+}
+
+#[test]
+fn test_search_ids_invalid_threshold() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let db = HashDb::new(feature_extractor);
+    let searcher = Searcher::new(&db, Cosine);
+
+    assert_eq!(
+        searcher.search_ids("foo", 0.0).unwrap_err(),
+        SearchError::InvalidThreshold(0.0)
+    );
+    assert_eq!(
+        searcher.ranked_search_ids("foo", 1.5).unwrap_err(),
+        SearchError::InvalidThreshold(1.5)
+    );
+}
+
+#[test]
+fn test_top_k_returns_best_matches() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in ["foo", "fooo", "foooo", "bar", "food", "fo", "baz"] {
+        db.insert(s.to_string());
+    }
+    let searcher = Searcher::new(&db, Cosine);
+
+    let top = searcher.top_k("foo", 3).unwrap();
+    assert_eq!(top.len(), 3);
+    assert_eq!(top[0].0, "foo");
+    assert!(approx_eq(top[0].1, 1.0));
+
+    // The top k must be a prefix of an exhaustive ranked search
+    let all = searcher.ranked_search("foo", f64::EPSILON).unwrap();
+    for (got, expected) in top.iter().zip(&all) {
+        assert_eq!(got.0, expected.0);
+        assert!(approx_eq(got.1, expected.1));
+    }
+
+    // Asking for more than exist returns everything sharing a feature with the query
+    let everything = searcher.top_k("foo", 100).unwrap();
+    assert_eq!(everything.len(), all.len());
+    assert!(everything.iter().all(|(s, _)| !s.starts_with("ba")));
+
+    assert!(searcher.top_k("foo", 0).unwrap().is_empty());
+
+    // The id variants return the same matches in the same order
+    let top_ids = searcher.top_k_ids("foo", 3).unwrap();
+    let resolved: Vec<(&str, f64)> = top_ids
+        .iter()
+        .map(|&(id, score)| (db.get_string(id).unwrap(), score))
+        .collect();
+    assert_eq!(resolved, top);
+    assert_eq!(
+        searcher
+            .top_k_ids_with_floor("foo", 100, 0.5)
+            .unwrap()
+            .len(),
+        searcher.top_k_with_floor("foo", 100, 0.5).unwrap().len()
+    );
+}
+
+/// Cosine, counting how often similarities are computed.
+#[derive(Default)]
+struct CountingCosine {
+    calls: AtomicUsize,
+}
+
+impl Measure for CountingCosine {
+    fn min_feature_size(&self, query_size: usize, alpha: f64) -> usize {
+        Cosine.min_feature_size(query_size, alpha)
+    }
+
+    fn max_feature_size(&self, query_size: usize, alpha: f64, db: &dyn Database) -> usize {
+        Cosine.max_feature_size(query_size, alpha, db)
+    }
+
+    fn minimum_common_feature_count(&self, query_size: usize, y_size: usize, alpha: f64) -> usize {
+        Cosine.minimum_common_feature_count(query_size, y_size, alpha)
+    }
+
+    fn similarity(&self, x: &[Spur], y: &[Spur]) -> f64 {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        Cosine.similarity(x, y)
+    }
+}
+
+#[test]
+fn test_top_k_scores_each_candidate_once() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in ["foo", "fooo", "foooo", "food", "fo", "f", "ofo", "oof"] {
+        db.insert(s.to_string());
+    }
+
+    let searcher = Searcher::new(&db, CountingCosine::default());
+    // Asking for everything lowers the threshold all the way to the floor
+    let everything = searcher.top_k("foo", 100).unwrap();
+    assert_eq!(
+        searcher.measure().calls.load(Ordering::Relaxed),
+        everything.len()
+    );
+}
+
+#[test]
+fn test_top_k_with_floor() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in ["foo", "fooo", "fo", "food"] {
+        db.insert(s.to_string());
+    }
+    let searcher = Searcher::new(&db, Jaccard);
+
+    let results = searcher.top_k_with_floor("foo", 10, 0.5).unwrap();
+    let expected = searcher.ranked_search("foo", 0.5).unwrap();
+    assert_eq!(results, expected);
+    assert!(results.iter().all(|&(_, score)| score >= 0.5));
+
+    assert_eq!(
+        searcher.top_k_with_floor("foo", 3, 0.0).unwrap_err(),
+        SearchError::InvalidThreshold(0.0)
+    );
+}
+
+#[test]
+fn test_batch_search_matches_individual_searches() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in ["foo", "fooo", "bar", "bars", "baz", "qux"] {
+        db.insert(s.to_string());
+    }
+    let searcher = Searcher::new(&db, Dice);
+    let queries = ["bar", "foo", "nothing", "", "bar"];
+
+    let batch = searcher.batch_search(&queries, 0.6).unwrap();
+    assert_eq!(batch.len(), queries.len());
+    for (query, results) in queries.iter().zip(&batch) {
+        assert_eq!(results, &searcher.search(query, 0.6).unwrap());
+    }
+
+    let ranked = searcher.batch_ranked_search(&queries, 0.6).unwrap();
+    assert_eq!(ranked.len(), queries.len());
+    for (query, results) in queries.iter().zip(&ranked) {
+        assert_eq!(results, &searcher.ranked_search(query, 0.6).unwrap());
+    }
+    assert_eq!(ranked[0][0].0, "bar");
+    assert!(ranked[2].is_empty());
+
+    let ranked_ids = searcher.batch_ranked_search_ids(&queries, 0.6).unwrap();
+    for (query, results) in queries.iter().zip(&ranked_ids) {
+        assert_eq!(results, &searcher.ranked_search_ids(query, 0.6).unwrap());
+    }
+}
+
+#[test]
+fn test_batch_search_invalid_threshold() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let db = HashDb::new(feature_extractor);
+    let searcher = Searcher::new(&db, Cosine);
+
+    assert_eq!(
+        searcher.batch_search(&["foo"], 1.1).unwrap_err(),
+        SearchError::InvalidThreshold(1.1)
+    );
+    assert_eq!(
+        searcher.batch_ranked_search(&["foo"], 0.0).unwrap_err(),
+        SearchError::InvalidThreshold(0.0)
+    );
+    assert!(searcher.batch_search(&[], 0.5).unwrap().is_empty());
+}
+
+#[test]
+fn test_frozen_db_concurrent_search() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in ["foo", "fooo", "food", "bar", "bars", "baz"] {
+        db.insert(s.to_string());
+    }
+    let queries = ["foo", "bar", "ba", "nothing"];
+    let expected: Vec<_> = {
+        let searcher = Searcher::new(&db, Dice);
+        queries
+            .iter()
+            .map(|query| searcher.ranked_search_ids(query, 0.5).unwrap())
+            .collect()
+    };
+
+    let frozen = db.freeze();
+    let searcher = Searcher::new(&frozen, Dice);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                scope.spawn(|| {
+                    queries
+                        .iter()
+                        .map(|query| searcher.ranked_search_ids(query, 0.5).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    });
+    assert_eq!(
+        searcher.batch_search(&queries, 0.5).unwrap()[1],
+        vec!["bar", "bars", "baz"]
+    );
+}
+
+#[test]
+fn test_ranked_search_verified() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(3, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in ["Acme Inc", "Acme Incorporated Holdings", "Apex Inc"] {
+        db.insert(s.to_string());
+    }
+    let searcher = Searcher::new(&db, Overlap);
+
+    // Overlap only looks at the shorter side, so the long name matches as well as the short
+    let unverified = searcher.ranked_search("Acme Inc.", 0.7).unwrap();
+    assert!(unverified
+        .iter()
+        .any(|&(s, _)| s == "Acme Incorporated Holdings"));
+
+    let blend = Verification::Blend {
+        measure: EditMeasure::Levenshtein,
+        weight: 0.5,
+    };
+    let blended = searcher
+        .ranked_search_verified("Acme Inc.", 0.7, &blend)
+        .unwrap();
+    assert_eq!(blended[0].0, "Acme Inc");
+    assert_eq!(blended[1].0, "Acme Incorporated Holdings");
+    assert!(blended[0].1 > blended[1].1);
+
+    let filter = Verification::Filter {
+        measure: EditMeasure::JaroWinkler,
+        min_similarity: 0.9,
+    };
+    let filtered = searcher
+        .ranked_search_verified("Acme Inc.", 0.7, &filter)
+        .unwrap();
+    let unverified_score = unverified
+        .iter()
+        .find(|&&(s, _)| s == "Acme Inc")
+        .unwrap()
+        .1;
+    assert_eq!(filtered, vec![("Acme Inc", unverified_score)]);
+}
+
+#[test]
+fn test_ranked_search_verified_invalid_parameters() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(3, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("foo".to_string());
+    let searcher = Searcher::new(&db, Dice);
+
+    let blend = Verification::Blend {
+        measure: EditMeasure::DamerauLevenshtein,
+        weight: 1.5,
+    };
+    assert_eq!(
+        searcher.ranked_search_verified("foo", 0.5, &blend),
+        Err(SearchError::InvalidVerification(1.5))
+    );
+    let filter = Verification::Filter {
+        measure: EditMeasure::Levenshtein,
+        min_similarity: 1.0,
+    };
+    assert_eq!(
+        searcher.ranked_search_verified("foo", 0.0, &filter),
+        Err(SearchError::InvalidThreshold(0.0))
+    );
+}
@@ -0,0 +1,163 @@
+#![cfg(feature = "server")]
+
+use serde_json::{json, Value};
+use simstring_rust::server::{MeasureKind, SearchServer, ServerConfig};
+use simstring_rust::{CharacterNgrams, HashDb};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::process::Command;
+use std::sync::Arc;
+
+fn test_server() -> SearchServer {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in ["foo", "fooo", "food", "bar", "bars"] {
+        db.insert(s.to_string());
+    }
+    let config = ServerConfig {
+        measure: MeasureKind::Dice,
+        threshold: 0.5,
+    };
+    SearchServer::new(db, config)
+}
+
+fn post(server: &SearchServer, path: &str, body: Value) -> (u16, Value) {
+    server.handle("POST", path, &body.to_string())
+}
+
+#[test]
+fn test_server_search_endpoints() {
+    let server = test_server();
+
+    let (status, body) = post(&server, "/search", json!({ "query": "foo" }));
+    assert_eq!(status, 200);
+    assert_eq!(body, json!({ "results": ["foo", "food", "fooo"] }));
+
+    let (_, body) = post(
+        &server,
+        "/ranked_search",
+        json!({ "query": "bar", "threshold": 0.6 }),
+    );
+    assert_eq!(
+        body["results"],
+        json!([
+            { "id": 3, "string": "bar", "score": 1.0 },
+            { "id": 4, "string": "bars", "score": 0.6666666666666666 },
+        ])
+    );
+
+    let (_, body) = post(&server, "/top_k", json!({ "query": "foo", "k": 1 }));
+    assert_eq!(
+        body["results"],
+        json!([{ "id": 0, "string": "foo", "score": 1.0 }])
+    );
+
+    let (_, body) = post(
+        &server,
+        "/batch",
+        json!({ "queries": ["bar", "nothing"], "threshold": 0.6 }),
+    );
+    assert_eq!(body["results"], json!([["bar", "bars"], []]));
+
+    let (_, body) = post(
+        &server,
+        "/batch",
+        json!({ "queries": ["bar", "nothing"], "threshold": 0.6, "ranked": true }),
+    );
+    assert_eq!(
+        body["results"],
+        json!([
+            [
+                { "id": 3, "string": "bar", "score": 1.0 },
+                { "id": 4, "string": "bars", "score": 0.6666666666666666 },
+            ],
+            [],
+        ])
+    );
+}
+
+#[test]
+fn test_server_insert_is_searchable() {
+    let server = test_server();
+
+    let (status, body) = post(&server, "/insert", json!({ "strings": ["bazaar", "baz"] }));
+    assert_eq!(status, 200);
+    assert_eq!(body, json!({ "ids": [5, 6] }));
+
+    let (_, body) = post(
+        &server,
+        "/search",
+        json!({ "query": "bazaar", "threshold": 1.0 }),
+    );
+    assert_eq!(body["results"], json!(["bazaar"]));
+}
+
+#[test]
+fn test_server_errors() {
+    let server = test_server();
+
+    let (status, body) = post(
+        &server,
+        "/search",
+        json!({ "query": "foo", "threshold": 2.0 }),
+    );
+    assert_eq!(status, 400);
+    assert!(body["error"].as_str().unwrap().contains("threshold"));
+
+    assert_eq!(server.handle("POST", "/search", "not json").0, 400);
+    assert_eq!(server.handle("GET", "/search", "").0, 405);
+    assert_eq!(server.handle("POST", "/missing", "{}").0, 404);
+}
+
+#[test]
+fn test_server_over_http() {
+    let server = test_server();
+    let http = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let addr = http.server_addr().to_ip().unwrap();
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| server.serve(&http, 2));
+
+        let body = json!({ "query": "bars", "threshold": 0.8 }).to_string();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST /ranked_search HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"));
+        let (_, payload) = response.split_once("\r\n\r\n").unwrap();
+        let payload: Value = serde_json::from_str(payload).unwrap();
+        assert_eq!(payload["results"][0]["string"], "bars");
+
+        http.unblock();
+        http.unblock();
+    });
+}
+
+#[test]
+fn test_server_binary_rejects_zero_ngram() {
+    let output = Command::new(env!("CARGO_BIN_EXE_simstring-server"))
+        .args(["-d", "missing.db", "-n", "0"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("n-gram size"));
+}
+
+#[test]
+fn test_server_binary_rejects_invalid_threshold() {
+    for threshold in ["0", "1.5", "NaN"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_simstring-server"))
+            .args(["-d", "missing.db", "-t", threshold])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("threshold must be in"));
+    }
+}
@@ -0,0 +1,310 @@
+use lasso::Rodeo;
+use simstring_rust::database::simstring_cpp;
+use simstring_rust::extractors::SimstringNgrams;
+use simstring_rust::{Cosine, Database, FeatureExtractor, HashDb, PersistError, Searcher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+fn temp_db_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "simstring_rust_cpp_{}_{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join("names.db")
+}
+
+fn resolve_features(extractor: &SimstringNgrams, text: &str) -> Vec<String> {
+    let mut interner = Rodeo::default();
+    let mut features: Vec<String> = extractor
+        .features(text, &mut interner)
+        .iter()
+        .map(|spur| interner.resolve(spur).to_string())
+        .collect();
+    features.sort();
+    features
+}
+
+#[test]
+fn test_simstring_ngrams_match_cpp_generator() {
+    let extractor = SimstringNgrams::new(2, false, false);
+    assert_eq!(
+        resolve_features(&extractor, "abab"),
+        vec!["ab", "ab2", "ba"]
+    );
+    // Strings shorter than n are padded when begin/end marks are disabled
+    assert_eq!(resolve_features(&extractor, "a"), vec!["a\u{1}"]);
+
+    let marked = SimstringNgrams::new(3, true, false);
+    assert_eq!(
+        resolve_features(&marked, "ab"),
+        vec!["\u{1}\u{1}a", "\u{1}ab", "ab\u{1}", "b\u{1}\u{1}"]
+    );
+}
+
+#[test]
+fn test_simstring_ngrams_byte_and_unicode_modes() {
+    let bytes = SimstringNgrams::new(1, false, false);
+    let unicode = SimstringNgrams::new(1, false, true);
+    let mut interner = Rodeo::default();
+
+    // "é" is two bytes in UTF-8 but a single char
+    assert_eq!(bytes.features("é", &mut interner).len(), 2);
+    assert_eq!(unicode.features("é", &mut interner).len(), 1);
+}
+
+#[test]
+fn test_write_and_read_roundtrip() {
+    let extractor = SimstringNgrams::new(3, true, false);
+    let mut db = HashDb::new(Arc::new(extractor.clone()));
+    for s in ["apple", "apply", "application", "banana", "bandana", ""] {
+        db.insert(s.to_string());
+    }
+
+    let path = temp_db_path("roundtrip");
+    simstring_cpp::write(db.iter().map(|(_, text)| text), &path, &extractor).unwrap();
+    let master = std::fs::read(&path).unwrap();
+    assert_eq!(&master[..4], b"SSDB");
+
+    let loaded = simstring_cpp::read(&path).unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert_eq!(loaded.total_strings(), db.total_strings());
+    assert_eq!(loaded.max_feature_len(), db.max_feature_len());
+    assert_eq!(
+        loaded.feature_extractor().descriptor(),
+        extractor.descriptor()
+    );
+    for id in 0..db.total_strings() {
+        assert_eq!(loaded.get_string(id), db.get_string(id));
+        assert_eq!(
+            loaded.get_features(id).unwrap().len(),
+            db.get_features(id).unwrap().len()
+        );
+    }
+
+    let expected_searcher = Searcher::new(&db, Cosine);
+    let expected = expected_searcher.ranked_search("appli", 0.4).unwrap();
+    let searcher = Searcher::new(&loaded, Cosine);
+    let actual = searcher.ranked_search("appli", 0.4).unwrap();
+    assert!(!expected.is_empty());
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_unicode_roundtrip() {
+    let extractor = SimstringNgrams::new(2, true, true);
+    let mut db = HashDb::new(Arc::new(WholeStringExtractor));
+    for s in ["café", "cafe", "naïve", "東京都"] {
+        db.insert(s.to_string());
+    }
+
+    let path = temp_db_path("unicode");
+    simstring_cpp::write(db.iter().map(|(_, text)| text), &path, &extractor).unwrap();
+    let loaded = simstring_cpp::read(&path).unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert_eq!(loaded.strings, db.strings);
+    let searcher = Searcher::new(&loaded, Cosine);
+    let results = searcher.ranked_search("東京", 0.5).unwrap();
+    assert_eq!(results[0].0, "東京都");
+}
+
+#[test]
+fn test_read_rejects_non_simstring_file() {
+    let path = temp_db_path("invalid");
+    std::fs::write(&path, b"definitely not a simstring database").unwrap();
+    let err = simstring_cpp::read(&path).unwrap_err();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert!(matches!(err, PersistError::InvalidMagic));
+}
+
+#[test]
+fn test_write_stores_size_slot_count() {
+    let extractor = SimstringNgrams::new(3, false, false);
+    let mut db = HashDb::new(Arc::new(extractor.clone()));
+    for s in ["abc", "abcdef", "abcdefgh"] {
+        db.insert(s.to_string());
+    }
+
+    let path = temp_db_path("max_size");
+    simstring_cpp::write(db.iter().map(|(_, text)| text), &path, &extractor).unwrap();
+    let master = std::fs::read(&path).unwrap();
+    // "abcdefgh" has 6 trigrams, so the C++ reader must see 7 size slots to open its index
+    let max_size = u32::from_le_bytes(master[32..36].try_into().unwrap());
+    let largest_index = path.with_file_name("names.db.6.cdb").exists();
+    let loaded = simstring_cpp::read(&path).unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert_eq!(max_size, 7);
+    assert!(largest_index);
+    let searcher = Searcher::new(&loaded, Cosine);
+    assert_eq!(searcher.search("abcdefgh", 1.0).unwrap(), vec!["abcdefgh"]);
+}
+
+#[test]
+fn test_read_rejects_invalid_utf8_entry() {
+    let extractor = SimstringNgrams::new(3, false, false);
+    let mut db = HashDb::new(Arc::new(extractor.clone()));
+    db.insert("abc".to_string());
+
+    let path = temp_db_path("invalid_utf8");
+    simstring_cpp::write(db.iter().map(|(_, text)| text), &path, &extractor).unwrap();
+    // Strings start right after the 36-byte header
+    let mut master = std::fs::read(&path).unwrap();
+    master[36] = 0xff;
+    std::fs::write(&path, master).unwrap();
+    let err = simstring_cpp::read(&path).unwrap_err();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert!(matches!(err, PersistError::Corrupt(reason) if reason.contains("UTF-8")));
+}
+
+#[test]
+fn test_read_rejects_impossible_entry_count() {
+    let extractor = SimstringNgrams::new(3, false, false);
+    let mut db = HashDb::new(Arc::new(extractor.clone()));
+    db.insert("abc".to_string());
+
+    let path = temp_db_path("entry_count");
+    simstring_cpp::write(db.iter().map(|(_, text)| text), &path, &extractor).unwrap();
+    // The number of entries is the u32 at offset 28 of the header
+    let mut master = std::fs::read(&path).unwrap();
+    master[28..32].copy_from_slice(&u32::MAX.to_le_bytes());
+    std::fs::write(&path, master).unwrap();
+    let err = simstring_cpp::read(&path).unwrap_err();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert!(matches!(err, PersistError::Corrupt(reason) if reason.contains("entries")));
+}
+
+/// Runs the C++ `simstring` tool on `path`, feeding `stdin` and returning the retrieved
+/// strings of every query, sorted.
+fn run_cpp_simstring(path: &Path, args: &[&str], stdin: &str) -> Vec<Vec<String>> {
+    let mut child = Command::new("simstring")
+        .arg("-d")
+        .arg(path)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("the C++ simstring tool must be on PATH (apt install simstring-bin)");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    // Each query prints its matches prefixed by a tab, then a "retrieved" summary line
+    let mut results = Vec::new();
+    let mut current = Vec::new();
+    for line in String::from_utf8(output.stdout).unwrap().lines() {
+        if let Some(matched) = line.strip_prefix('\t') {
+            current.push(matched.to_string());
+        } else if line.contains("retrieved") {
+            current.sort();
+            results.push(std::mem::take(&mut current));
+        }
+    }
+    results
+}
+
+const CPP_NAMES: &[&str] = &[
+    "Acme Inc",
+    "Acme Incorporated",
+    "Acme Industries Holdings",
+    "Apex Inc",
+    "Globex Corporation",
+    "Initech",
+    "Umbrella Corporation",
+];
+const CPP_QUERIES: &[&str] = &["Acme Inc.", "Globex Corp", "Umbrella Corporation", "Initek"];
+
+fn rust_results(db: &HashDb) -> Vec<Vec<String>> {
+    let searcher = Searcher::new(db, Cosine);
+    CPP_QUERIES
+        .iter()
+        .map(|query| {
+            let mut matches: Vec<String> = searcher
+                .search(query, 0.6)
+                .unwrap()
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+            matches.sort();
+            matches
+        })
+        .collect()
+}
+
+#[test]
+#[ignore = "requires the C++ simstring tool (simstring-bin)"]
+fn test_read_database_built_by_cpp_tool() {
+    let path = temp_db_path("cpp_built");
+    let mut child = Command::new("simstring")
+        .arg("-b")
+        .arg("-d")
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("the C++ simstring tool must be on PATH (apt install simstring-bin)");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(CPP_NAMES.join("\n").as_bytes())
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+
+    let expected = run_cpp_simstring(
+        &path,
+        &["-s", "cosine", "-t", "0.6"],
+        &CPP_QUERIES.join("\n"),
+    );
+    let loaded = simstring_cpp::read(&path).unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert_eq!(loaded.total_strings(), CPP_NAMES.len());
+    assert_eq!(expected.len(), CPP_QUERIES.len());
+    assert_eq!(rust_results(&loaded), expected);
+}
+
+#[test]
+#[ignore = "requires the C++ simstring tool (simstring-bin)"]
+fn test_cpp_tool_reads_exported_database() {
+    // The C++ tool's defaults: trigrams over bytes, without begin/end marks
+    let extractor = SimstringNgrams::new(3, false, false);
+    let mut db = HashDb::new(Arc::new(extractor.clone()));
+    for name in CPP_NAMES {
+        db.insert(name.to_string());
+    }
+
+    let path = temp_db_path("cpp_export");
+    simstring_cpp::write(db.iter().map(|(_, text)| text), &path, &extractor).unwrap();
+    let actual = run_cpp_simstring(
+        &path,
+        &["-s", "cosine", "-t", "0.6"],
+        &CPP_QUERIES.join("\n"),
+    );
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    // "Acme Industries Holdings" has the most trigrams, so this also covers the largest index
+    assert_eq!(actual, rust_results(&db));
+}
+
+/// Extractor used to show that exports do not depend on the source database's extractor.
+struct WholeStringExtractor;
+
+impl FeatureExtractor for WholeStringExtractor {
+    fn features(&self, text: &str, interner: &mut Rodeo) -> Vec<lasso::Spur> {
+        vec![interner.get_or_intern(text)]
+    }
+}
@@ -0,0 +1,94 @@
+use simstring_rust::distance::{levenshtein, levenshtein_similarity};
+use simstring_rust::suggest::{Suggester, SuggesterOptions};
+use simstring_rust::{CharacterNgrams, Cosine, Dice, HashDb, SearchError};
+use std::sync::Arc;
+
+fn dictionary() -> HashDb<u64> {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::with_payloads(feature_extractor);
+    for (word, count) in [
+        ("receive", 900),
+        ("recipe", 400),
+        ("deceive", 120),
+        ("relieve", 300),
+        ("receiver", 80),
+        ("revive", 10),
+    ] {
+        db.insert_with_payload(word.to_string(), count);
+    }
+    db
+}
+
+#[test]
+fn test_levenshtein() {
+    assert_eq!(levenshtein("kitten", "sitting"), 3);
+    assert_eq!(levenshtein("", "abc"), 3);
+    assert_eq!(levenshtein("flaw", "flaw"), 0);
+    // Distances count chars, not bytes
+    assert_eq!(levenshtein("café", "cafe"), 1);
+    assert_eq!(levenshtein_similarity("", ""), 1.0);
+    assert_eq!(levenshtein_similarity("abcd", "abce"), 0.75);
+}
+
+#[test]
+fn test_suggester_ranks_corrections() {
+    let db = dictionary();
+    let suggester = Suggester::new(&db, Dice, SuggesterOptions::default()).unwrap();
+    let suggestions = suggester.suggest("recive").unwrap();
+
+    assert_eq!(suggestions[0].word, "receive");
+    assert_eq!(suggestions[0].frequency, 900);
+    assert_eq!(suggestions[0].distance, 1);
+    assert!(suggestions.iter().all(|s| s.distance <= 2));
+    assert!(suggestions
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+}
+
+#[test]
+fn test_suggester_frequency_breaks_near_ties() {
+    let db = dictionary();
+    let options = SuggesterOptions {
+        alpha: 0.3,
+        max_distance: None,
+        frequency_weight: 0.9,
+        max_suggestions: 2,
+    };
+    let suggester = Suggester::new(&db, Cosine, options).unwrap();
+    let words: Vec<&str> = suggester
+        .suggest("reeive")
+        .unwrap()
+        .iter()
+        .map(|s| s.word)
+        .collect();
+
+    assert_eq!(words, vec!["receive", "recipe"]);
+}
+
+#[test]
+fn test_suggester_requires_frequency_payloads() {
+    let feature_extractor = Arc::new(CharacterNgrams::new(2, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    db.insert("receive".to_string());
+
+    let suggester = Suggester::new(&db, Dice, SuggesterOptions::default()).unwrap();
+    assert!(matches!(
+        suggester.suggest("recieve"),
+        Err(SearchError::PayloadTypeMismatch(_))
+    ));
+}
+
+#[test]
+fn test_suggester_rejects_invalid_frequency_weight() {
+    let db = dictionary();
+    for weight in [-0.1, 1.5, f64::NAN] {
+        let options = SuggesterOptions {
+            frequency_weight: weight,
+            ..SuggesterOptions::default()
+        };
+        assert!(matches!(
+            Suggester::new(&db, Dice, options),
+            Err(SearchError::InvalidFrequencyWeight(_))
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+use simstring_rust::tagger::{Resolution, Tag, Tagger, TaggerOptions};
+use simstring_rust::{CharacterNgrams, Cosine, HashDb, SearchError};
+use std::sync::Arc;
+
+fn concept_db() -> HashDb {
+    let feature_extractor = Arc::new(CharacterNgrams::new(3, "$"));
+    let mut db = HashDb::new(feature_extractor);
+    for s in [
+        "heart attack",
+        "heart",
+        "attack",
+        "type 2 diabetes",
+        "diabetes mellitus",
+        "aspirin",
+    ] {
+        db.insert(s.to_string());
+    }
+    db
+}
+
+fn spans<'a>(text: &'a str, tags: &[Tag]) -> Vec<(&'a str, String)> {
+    tags.iter()
+        .map(|tag| (&text[tag.byte_start..tag.byte_end], tag.matched.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_tagger_prefers_longest_match() {
+    let db = concept_db();
+    let options = TaggerOptions {
+        alpha: 0.8,
+        ..TaggerOptions::default()
+    };
+    let tagger = Tagger::new(&db, Cosine, options);
+    let text = "Suspected heart attack; given aspirin. History: type-2 diabetes.";
+    let tags = tagger.tag(text).unwrap();
+
+    assert_eq!(
+        spans(text, &tags),
+        vec![
+            ("heart attack", "heart attack".to_string()),
+            ("aspirin", "aspirin".to_string()),
+            ("type-2 diabetes", "type 2 diabetes".to_string()),
+        ]
+    );
+    assert_eq!(tags[0].id, 0);
+    assert_eq!(tags[0].score, 1.0);
+    assert!(tags[2].score < 1.0);
+}
+
+#[test]
+fn test_tagger_best_score_first() {
+    let db = concept_db();
+    let options = TaggerOptions {
+        alpha: 0.5,
+        resolution: Resolution::BestScoreFirst,
+        ..TaggerOptions::default()
+    };
+    let tagger = Tagger::new(&db, Cosine, options);
+    let text = "heart attacks";
+    let tags = tagger.tag(text).unwrap();
+
+    // "heart" matches exactly, so it beats the longer but inexact "heart attacks"
+    assert_eq!(spans(text, &tags)[0], ("heart", "heart".to_string()));
+    assert!(tags.len() <= 2);
+}
+
+#[test]
+fn test_tagger_character_offsets() {
+    let db = concept_db();
+    let tagger = Tagger::new(&db, Cosine, TaggerOptions::default());
+    let text = "Café → aspirin";
+    let tags = tagger.tag(text).unwrap();
+
+    assert_eq!(tags.len(), 1);
+    assert_eq!((tags[0].start, tags[0].end), (7, 14));
+    assert_eq!((tags[0].byte_start, tags[0].byte_end), (10, 17));
+}
+
+#[test]
+fn test_tagger_window_limit_and_empty_text() {
+    let db = concept_db();
+    let options = TaggerOptions {
+        max_window: 1,
+        ..TaggerOptions::default()
+    };
+    let tagger = Tagger::new(&db, Cosine, options);
+    let text = "heart attack";
+
+    assert_eq!(
+        spans(text, &tagger.tag(text).unwrap()),
+        vec![
+            ("heart", "heart".to_string()),
+            ("attack", "attack".to_string()),
+        ]
+    );
+    assert!(tagger.tag("").unwrap().is_empty());
+}
+
+#[test]
+fn test_tagger_invalid_threshold() {
+    let db = concept_db();
+    let options = TaggerOptions {
+        alpha: 0.0,
+        ..TaggerOptions::default()
+    };
+    let tagger = Tagger::new(&db, Cosine, options);
+    assert_eq!(
+        tagger.tag("heart").unwrap_err(),
+        SearchError::InvalidThreshold(0.0)
+    );
+}